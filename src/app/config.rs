@@ -4,6 +4,8 @@ use strum_macros::{Display, EnumString};
 use toml::ser::Error;
 
 use crate::da::da_layers::DALayer;
+use crate::utils::constants::{LOG_DEFAULT_MAX_FILES, LOG_DEFAULT_MAX_SIZE_MB, MADARA_REPO_NAME, MADARA_REPO_ORG};
+use crate::utils::madara::GITHUB_BASE_URL;
 
 #[derive(Serialize, Deserialize)]
 pub struct AppChainConfig {
@@ -11,11 +13,29 @@ pub struct AppChainConfig {
     pub base_path: String,
     pub mode: RollupMode,
     pub da_layer: DALayer,
+    /// Where the Madara node runs, configs created before
+    /// this was added run natively
+    #[serde(default)]
+    pub runtime: Runtime,
     /// Stores commit hash of madara app chain build
     pub madara_version: String,
+    /// Git repository Madara is fetched from, forks can be used
+    #[serde(default = "default_madara_repo")]
+    pub madara_repo: String,
+    /// Tag the Madara version was picked with, releases are downloaded by tag
+    pub madara_tag: Option<String>,
+    /// Port of the Starknet JSON-RPC, allocated at init so that app chains can run side by side
+    pub rpc_port: u16,
+    /// Port the node listens on for its peers
+    pub p2p_port: u16,
+    /// Port of the Prometheus metrics
+    pub prometheus_port: u16,
     /// Maintains version of config, will help in handling edge
     /// cases when attributes are added / removed from struct
     pub config_version: ConfigVersion,
+    /// Rotation of the node logs
+    #[serde(default)]
+    pub logs: LogsConfig,
 }
 
 impl AppChainConfig {
@@ -24,15 +44,65 @@ impl AppChainConfig {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, EnumIter, Display, Clone, EnumString, clap::ValueEnum)]
+pub fn default_madara_repo() -> String {
+    format!("{}/{}/{}", GITHUB_BASE_URL, MADARA_REPO_ORG, MADARA_REPO_NAME)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogsConfig {
+    /// Size in MiB after which the log file is rotated
+    pub max_size_mb: u64,
+    /// Number of rotated log files kept, older ones are deleted
+    pub max_files: u32,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        LogsConfig { max_size_mb: LOG_DEFAULT_MAX_SIZE_MB, max_files: LOG_DEFAULT_MAX_FILES }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, EnumIter, Display, Clone, PartialEq, EnumString, clap::ValueEnum)]
 pub enum RollupMode {
+    /// State transitions are only checked by the nodes of the app chain
     Sovereign,
-    // Validity,
-    // Validium,
+    /// Proofs are verified on Ethereum, which also stores the data
+    Validity,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl RollupMode {
+    /// Mode as expected in the DA config of Madara
+    pub fn da_mode(&self) -> String {
+        self.to_string().to_lowercase()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, EnumIter, Display, Clone, PartialEq, EnumString, clap::ValueEnum)]
+pub enum Runtime {
+    /// Madara runs as a process on the host
+    #[default]
+    Native,
+    /// Madara runs inside a Docker container
+    Docker,
+}
+
+#[derive(Debug, Serialize, Deserialize, Display, Clone, PartialEq)]
 pub enum ConfigVersion {
     Version1,
     Version2,
+    /// The runtime is always set
+    Version3,
+    /// The Madara repository is always set
+    Version4,
+    /// The rotation of the logs is always set
+    Version5,
+    /// The RPC, P2P and Prometheus ports are always set
+    Version6,
+}
+
+impl ConfigVersion {
+    /// Version of the configs created by this CLI, older ones are migrated when loaded
+    pub fn latest() -> Self {
+        ConfigVersion::Version6
+    }
 }
@@ -0,0 +1,193 @@
+use toml::{Table, Value};
+
+use crate::app::config::{default_madara_repo, ConfigVersion, LogsConfig, Runtime};
+use crate::utils::constants::{MADARA_DEFAULT_P2P_PORT, MADARA_DEFAULT_PROMETHEUS_PORT, MADARA_DEFAULT_RPC_PORT};
+use crate::utils::errors::TomlError;
+
+/// Commit the Version1 configs were built with, the commit stored in them was incorrect
+const VERSION1_MADARA_COMMIT: &str = "5de416aeb2d9e4297e58f7f2dff99aeae521855e";
+
+/// Upgrades a config to the next version
+type Migration = fn(&mut Table) -> Result<(), TomlError>;
+
+/// Returns the next version and the migration to it, `None` for the latest version
+fn get_migration(version: &ConfigVersion) -> Option<(ConfigVersion, Migration)> {
+    match version {
+        ConfigVersion::Version1 => Some((ConfigVersion::Version2, version1_to_version2)),
+        ConfigVersion::Version2 => Some((ConfigVersion::Version3, version2_to_version3)),
+        ConfigVersion::Version3 => Some((ConfigVersion::Version4, version3_to_version4)),
+        ConfigVersion::Version4 => Some((ConfigVersion::Version5, version4_to_version5)),
+        ConfigVersion::Version5 => Some((ConfigVersion::Version6, version5_to_version6)),
+        ConfigVersion::Version6 => None,
+    }
+}
+
+pub fn get_config_version(config: &Table) -> Result<ConfigVersion, TomlError> {
+    let version = config.get("config_version").ok_or(TomlError::MissingField("config_version".to_string()))?;
+    Ok(version.clone().try_into()?)
+}
+
+/// Migrates the config step by step to the latest version.
+/// Returns the versions it went through, empty if it was up to date.
+pub fn migrate(config: &mut Table) -> Result<Vec<ConfigVersion>, TomlError> {
+    let mut version = get_config_version(config)?;
+    let mut versions = vec![];
+
+    while let Some((next_version, migration)) = get_migration(&version) {
+        migration(config)?;
+        config.insert("config_version".to_string(), Value::String(next_version.to_string()));
+        versions.push(next_version.clone());
+        version = next_version;
+    }
+
+    Ok(versions)
+}
+
+fn version1_to_version2(config: &mut Table) -> Result<(), TomlError> {
+    // there was a bug in Version1 where the incorrect commit was
+    // going inside the toml file, so store the correct commit
+    config.insert("madara_version".to_string(), Value::String(VERSION1_MADARA_COMMIT.to_string()));
+    Ok(())
+}
+
+fn version2_to_version3(config: &mut Table) -> Result<(), TomlError> {
+    // configs created before the runtime was added run natively
+    config.entry("runtime").or_insert(Value::String(Runtime::Native.to_string()));
+    Ok(())
+}
+
+fn version3_to_version4(config: &mut Table) -> Result<(), TomlError> {
+    // configs created before forks were supported use the upstream repository
+    config.entry("madara_repo").or_insert(Value::String(default_madara_repo()));
+    Ok(())
+}
+
+fn version4_to_version5(config: &mut Table) -> Result<(), TomlError> {
+    // configs created before the logs were rotated use the default rotation
+    config.entry("logs").or_insert(Value::try_from(LogsConfig::default())?);
+    Ok(())
+}
+
+fn version5_to_version6(config: &mut Table) -> Result<(), TomlError> {
+    // configs created before the ports were configurable run on the defaults of Madara
+    config.entry("rpc_port").or_insert(Value::Integer(MADARA_DEFAULT_RPC_PORT.into()));
+    config.entry("p2p_port").or_insert(Value::Integer(MADARA_DEFAULT_P2P_PORT.into()));
+    config.entry("prometheus_port").or_insert(Value::Integer(MADARA_DEFAULT_PROMETHEUS_PORT.into()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::AppChainConfig;
+
+    /// Config as written by the first version of the CLI
+    const VERSION1_CONFIG: &str = r#"
+        app_chain = "test"
+        base_path = "/tmp/test"
+        mode = "Sovereign"
+        da_layer = "Avail"
+        madara_version = "0000000000000000000000000000000000000000"
+        config_version = "Version1"
+    "#;
+
+    fn config_at(version: &str) -> Table {
+        let mut config: Table = toml::from_str(VERSION1_CONFIG).unwrap();
+        config.insert("config_version".to_string(), Value::String(version.to_string()));
+        config
+    }
+
+    #[test]
+    fn version1_to_version2_fixes_the_commit() {
+        let mut config = config_at("Version1");
+        version1_to_version2(&mut config).unwrap();
+        assert_eq!(config["madara_version"].as_str(), Some(VERSION1_MADARA_COMMIT));
+    }
+
+    #[test]
+    fn version2_to_version3_runs_natively() {
+        let mut config = config_at("Version2");
+        version2_to_version3(&mut config).unwrap();
+        assert_eq!(config["runtime"].as_str(), Some("Native"));
+    }
+
+    #[test]
+    fn version2_to_version3_keeps_the_runtime() {
+        let mut config = config_at("Version2");
+        config.insert("runtime".to_string(), Value::String(Runtime::Docker.to_string()));
+        version2_to_version3(&mut config).unwrap();
+        assert_eq!(config["runtime"].as_str(), Some("Docker"));
+    }
+
+    #[test]
+    fn version3_to_version4_uses_upstream() {
+        let mut config = config_at("Version3");
+        version3_to_version4(&mut config).unwrap();
+        assert_eq!(config["madara_repo"].as_str(), Some(default_madara_repo().as_str()));
+    }
+
+    #[test]
+    fn version4_to_version5_rotates_logs_by_default() {
+        let mut config = config_at("Version4");
+        version4_to_version5(&mut config).unwrap();
+        let logs: LogsConfig = config["logs"].clone().try_into().unwrap();
+        assert_eq!(logs.max_size_mb, LogsConfig::default().max_size_mb);
+        assert_eq!(logs.max_files, LogsConfig::default().max_files);
+    }
+
+    #[test]
+    fn version5_to_version6_uses_the_default_ports() {
+        let mut config = config_at("Version5");
+        version5_to_version6(&mut config).unwrap();
+        assert_eq!(config["rpc_port"].as_integer(), Some(MADARA_DEFAULT_RPC_PORT.into()));
+        assert_eq!(config["p2p_port"].as_integer(), Some(MADARA_DEFAULT_P2P_PORT.into()));
+        assert_eq!(config["prometheus_port"].as_integer(), Some(MADARA_DEFAULT_PROMETHEUS_PORT.into()));
+    }
+
+    #[test]
+    fn migrate_goes_through_every_version() {
+        let mut config = config_at("Version1");
+        let versions = migrate(&mut config).unwrap();
+        assert_eq!(
+            versions,
+            vec![
+                ConfigVersion::Version2,
+                ConfigVersion::Version3,
+                ConfigVersion::Version4,
+                ConfigVersion::Version5,
+                ConfigVersion::Version6
+            ]
+        );
+
+        let config: AppChainConfig = Value::Table(config).try_into().unwrap();
+        assert_eq!(config.config_version, ConfigVersion::Version6);
+        assert_eq!(config.madara_version, VERSION1_MADARA_COMMIT);
+        assert_eq!(config.runtime, Runtime::Native);
+        assert_eq!(config.rpc_port, MADARA_DEFAULT_RPC_PORT);
+    }
+
+    #[test]
+    fn migrate_from_an_intermediate_version() {
+        let mut config = config_at("Version4");
+        let versions = migrate(&mut config).unwrap();
+        assert_eq!(versions, vec![ConfigVersion::Version5, ConfigVersion::Version6]);
+        // the commit is only fixed for Version1 configs
+        assert_eq!(config["madara_version"].as_str(), Some("0000000000000000000000000000000000000000"));
+    }
+
+    #[test]
+    fn migrate_keeps_the_latest_version() {
+        let mut config = config_at("Version1");
+        migrate(&mut config).unwrap();
+        let migrated = config.clone();
+        assert!(migrate(&mut config).unwrap().is_empty());
+        assert_eq!(config, migrated);
+    }
+
+    #[test]
+    fn missing_version_is_an_error() {
+        let mut config = config_at("Version1");
+        config.remove("config_version");
+        assert!(matches!(migrate(&mut config), Err(TomlError::MissingField(_))));
+    }
+}
@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+
+use crate::app::config::AppChainConfig;
+use crate::binary::provider::{BinaryError, BinaryProvider, MadaraBinary};
+
+pub struct LocalProvider {
+    pub path: PathBuf,
+}
+
+#[async_trait]
+impl BinaryProvider for LocalProvider {
+    async fn get_binary(&self, _config: &AppChainConfig) -> Result<MadaraBinary, BinaryError> {
+        if !self.path.is_file() {
+            return Err(BinaryError::BinaryNotFound(self.path.clone()));
+        }
+
+        log::info!("Using Madara binary at {}", self.path.display());
+        Ok(MadaraBinary { path: self.path.clone(), configs_path: None })
+    }
+}
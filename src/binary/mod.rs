@@ -0,0 +1,7 @@
+pub mod provider;
+
+pub mod release;
+
+pub mod local;
+
+pub mod source;
@@ -0,0 +1,156 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::app::config::AppChainConfig;
+use crate::binary::local::LocalProvider;
+use crate::binary::release::ReleaseProvider;
+use crate::binary::source::SourceProvider;
+use crate::utils::errors::MadaraError;
+use crate::utils::paths::get_madara_cache_home;
+
+/// Where the Madara binary used to run the app chain comes from
+#[derive(Debug, Clone)]
+pub enum BinarySource {
+    /// Prebuilt release artifact pinned to the app chain's Madara tag
+    Release,
+    /// Binary already present on the machine
+    Local(PathBuf),
+    /// Clone the Madara repo and build it
+    Source,
+}
+
+impl BinarySource {
+    pub fn from_flags(madara_bin: &Option<PathBuf>, build_from_source: bool) -> Self {
+        match (madara_bin, build_from_source) {
+            (Some(path), _) => BinarySource::Local(path.clone()),
+            (None, true) => BinarySource::Source,
+            (None, false) => BinarySource::Release,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BinaryError {
+    #[error("Failed to download Madara binary: {0}")]
+    FailedToDownload(#[from] reqwest::Error),
+    #[error("No Madara release found at {0}, use --build-from-source or --madara-bin instead")]
+    ReleaseNotFound(String),
+    #[error("Madara {0} has no prebuilt release, use --build-from-source or pin a tag with upgrade --madara-version")]
+    NotTagged(String),
+    #[error("Madara {0} is not cached and can't be downloaded, use --build-from-source or --madara-bin instead")]
+    NotCached(String),
+    #[error("Invalid checksum file at {0}")]
+    InvalidChecksumFile(String),
+    #[error("Checksum mismatch for Madara binary, expected {expected} but got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("Madara binary not found at {0}")]
+    BinaryNotFound(PathBuf),
+    #[error("Failed to build Madara: {0}")]
+    FailedToBuild(#[from] MadaraError),
+    #[error("Failed to access binary cache: {0}")]
+    FailedToAccessCache(#[from] io::Error),
+}
+
+/// Madara binary ready to run an app chain
+#[derive(Debug, Clone)]
+pub struct MadaraBinary {
+    pub path: PathBuf,
+    /// Chain configs shipped with the binary, the remote ones are used if not present
+    pub configs_path: Option<PathBuf>,
+}
+
+#[async_trait]
+pub trait BinaryProvider {
+    async fn get_binary(&self, config: &AppChainConfig) -> Result<MadaraBinary, BinaryError>;
+}
+
+pub struct BinaryFactory;
+
+impl BinaryFactory {
+    pub fn new_provider(source: &BinarySource) -> Box<dyn BinaryProvider + Send + Sync> {
+        match source {
+            BinarySource::Release => Box::new(ReleaseProvider {}),
+            BinarySource::Local(path) => Box::new(LocalProvider { path: path.clone() }),
+            BinarySource::Source => Box::new(SourceProvider {}),
+        }
+    }
+}
+
+/// Binaries are cached per version at "{cache home}/bin/{version}/madara"
+pub fn get_cached_binary_path(version: &str) -> Result<PathBuf, io::Error> {
+    Ok(get_madara_cache_home()?.join("bin").join(version).join("madara"))
+}
+
+/// Versions with a cached binary, most recently cached first
+pub fn get_cached_versions() -> Result<Vec<String>, io::Error> {
+    let bin_path = get_madara_cache_home()?.join("bin");
+    if !bin_path.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut versions = Vec::new();
+    for entry in fs::read_dir(bin_path)? {
+        let binary_path = entry?.path().join("madara");
+        if let (Ok(metadata), Some(version)) = (fs::metadata(&binary_path), get_version_name(&binary_path)) {
+            versions.push((metadata.modified()?, version));
+        }
+    }
+    versions.sort_by(|(time, _), (other_time, _)| other_time.cmp(time));
+
+    Ok(versions.into_iter().map(|(_, version)| version).collect())
+}
+
+fn get_version_name(binary_path: &Path) -> Option<String> {
+    binary_path.parent()?.file_name()?.to_str().map(|version| version.to_string())
+}
+
+/// The sha256 of a cached binary is stored next to it, e.g. "bin/{version}/madara.sha256"
+fn get_checksum_path(binary_path: &Path) -> PathBuf {
+    binary_path.with_extension("sha256")
+}
+
+/// Returns the cached binary of the version if its content still matches the checksum stored
+/// when it was cached. Binaries without a checksum or which changed since are not used.
+pub fn get_verified_cached_binary(version: &str) -> Result<Option<PathBuf>, io::Error> {
+    let binary_path = get_cached_binary_path(version)?;
+    if !binary_path.is_file() {
+        return Ok(None);
+    }
+
+    let expected = match fs::read_to_string(get_checksum_path(&binary_path)) {
+        Ok(expected) => expected.trim().to_lowercase(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            log::warn!("Cached Madara binary {} has no checksum, ignoring it", binary_path.display());
+            return Ok(None);
+        }
+        Err(err) => return Err(err),
+    };
+    if hex::encode(Sha256::digest(fs::read(&binary_path)?)) != expected {
+        log::warn!("Cached Madara binary {} doesn't match its checksum, ignoring it", binary_path.display());
+        return Ok(None);
+    }
+
+    Ok(Some(binary_path))
+}
+
+/// Stores the binary in the cache with its checksum and makes it executable
+pub fn cache_binary(version: &str, content: &[u8]) -> Result<PathBuf, io::Error> {
+    let binary_path = get_cached_binary_path(version)?;
+    if let Some(parent) = binary_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // write to a temporary file first so that an interrupted write never leaves a broken binary
+    let tmp_path = binary_path.with_extension("tmp");
+    fs::write(&tmp_path, content)?;
+    fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o755))?;
+    fs::write(get_checksum_path(&binary_path), hex::encode(Sha256::digest(content)))?;
+    fs::rename(&tmp_path, &binary_path)?;
+
+    Ok(binary_path)
+}
@@ -0,0 +1,75 @@
+use std::env::consts::{ARCH, OS};
+
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use sha2::{Digest, Sha256};
+
+use crate::app::config::AppChainConfig;
+use crate::binary::provider::{cache_binary, get_verified_cached_binary, BinaryError, BinaryProvider, MadaraBinary};
+use crate::utils::github::{get_local_repo_path, is_offline};
+use crate::utils::madara::get_madara_commit;
+
+pub struct ReleaseProvider;
+
+#[async_trait]
+impl BinaryProvider for ReleaseProvider {
+    async fn get_binary(&self, config: &AppChainConfig) -> Result<MadaraBinary, BinaryError> {
+        let version = get_madara_commit(config);
+        if let Some(binary_path) = get_verified_cached_binary(version)? {
+            log::info!("Using cached Madara binary {}", binary_path.display());
+            return Ok(MadaraBinary { path: binary_path, configs_path: None });
+        }
+
+        // releases are published under their tag only, building from source takes a while so it
+        // is left to the user to ask for it
+        let release = config.madara_tag.as_ref().ok_or(BinaryError::NotTagged(version.to_string()))?;
+
+        if is_offline() || get_local_repo_path(&config.madara_repo).is_some() {
+            return Err(BinaryError::NotCached(version.to_string()));
+        }
+
+        let url = format!(
+            "{}/releases/download/{}/{}",
+            config.madara_repo.trim_end_matches('/').trim_end_matches(".git"),
+            release,
+            get_artifact_name()
+        );
+        let client = Client::new();
+
+        let checksum_url = format!("{}.sha256", url);
+        let checksum_file = download(&client, &checksum_url).await?;
+        // the checksum file follows the `sha256sum` format: "<hash>  <file name>"
+        let expected = String::from_utf8_lossy(&checksum_file)
+            .split_whitespace()
+            .next()
+            .map(|checksum| checksum.to_lowercase())
+            .ok_or(BinaryError::InvalidChecksumFile(checksum_url))?;
+
+        log::info!("⬇️ Downloading Madara {} from {}", version, url);
+        let binary = download(&client, &url).await?;
+
+        let actual = hex::encode(Sha256::digest(&binary));
+        if actual != expected {
+            return Err(BinaryError::ChecksumMismatch { expected, actual });
+        }
+
+        let binary_path = cache_binary(version, &binary)?;
+        log::info!("✅ Madara binary verified and cached at {}", binary_path.display());
+
+        Ok(MadaraBinary { path: binary_path, configs_path: None })
+    }
+}
+
+/// Release artifacts are named after the platform, e.g. "madara-x86_64-linux"
+fn get_artifact_name() -> String {
+    format!("madara-{}-{}", ARCH, OS)
+}
+
+async fn download(client: &Client, url: &str) -> Result<Vec<u8>, BinaryError> {
+    let response = client.get(url).header("User-Agent", "reqwest").send().await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Err(BinaryError::ReleaseNotFound(url.to_string()));
+    }
+
+    Ok(response.error_for_status()?.bytes().await?.to_vec())
+}
@@ -0,0 +1,27 @@
+use std::fs;
+
+use async_trait::async_trait;
+
+use crate::app::config::AppChainConfig;
+use crate::binary::provider::{cache_binary, BinaryError, BinaryProvider, MadaraBinary};
+use crate::utils::madara::{clone_madara_and_build_repo, get_madara_clone_path, get_madara_commit};
+
+pub struct SourceProvider;
+
+#[async_trait]
+impl BinaryProvider for SourceProvider {
+    async fn get_binary(&self, config: &AppChainConfig) -> Result<MadaraBinary, BinaryError> {
+        clone_madara_and_build_repo(config)?;
+
+        let madara_path = get_madara_clone_path(&config.madara_repo)?;
+        let binary_path = madara_path.join("target").join("release").join("madara");
+        if !binary_path.is_file() {
+            return Err(BinaryError::BinaryNotFound(binary_path));
+        }
+
+        // cache the build so that later runs don't need to rebuild
+        cache_binary(get_madara_commit(config), &fs::read(&binary_path)?)?;
+
+        Ok(MadaraBinary { path: binary_path, configs_path: Some(madara_path.join("configs")) })
+    }
+}
@@ -0,0 +1,508 @@
+use std::path::PathBuf;
+use std::process::Command;
+use std::{env, fs, io};
+
+use inquire::InquireError;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+use toml::{Table, Value as TomlValue};
+
+use crate::app::config::AppChainConfig;
+use crate::cli::list::{get_app_chain_dirs, get_apps_list};
+use crate::cli::prompt::{get_option, CHAIN_NAME};
+use crate::da::da_layers::{DAFactory, DaError};
+use crate::utils::constants::{APP_CONFIG_NAME, APP_DA_CONFIG_NAME};
+use crate::utils::errors::{ProcessError, TomlError};
+use crate::utils::keystore::write_private_file;
+use crate::utils::paths::get_app_home;
+use crate::utils::process::get_running_node;
+use crate::utils::toml::migrate_app_config;
+
+/// Prefix of the keys of the DA config
+const DA_PREFIX: &str = "da.";
+/// Keys that are never changed by hand
+const READ_ONLY_KEYS: [&str; 3] = ["app_chain", "config_version", "da.mode"];
+/// Keys that can't change while the node is running, including the DA services the CLI manages
+const IMMUTABLE_KEYS: [&str; 6] = ["base_path", "mode", "da_layer", "runtime", "da.local_anvil", "da.local_devnet"];
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to get input: {0}")]
+    FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] io::Error),
+    #[error("Failed to migrate the config of {0}: {1}")]
+    FailedToMigrate(String, TomlError),
+    #[error("Invalid config: {0}")]
+    InvalidConfig(#[from] TomlError),
+    #[error("Invalid DA config: {0}")]
+    InvalidDaConfig(#[from] DaError),
+    #[error("Unknown key `{0}`")]
+    UnknownKey(String),
+    #[error("Invalid value `{1}` for `{0}`")]
+    InvalidValue(String, String),
+    #[error("`{0}` can't be changed")]
+    ReadOnlyKey(String),
+    #[error("`{0}` can't be changed while {1} is running, stop it first")]
+    ImmutableWhileRunning(String, String),
+    #[error("Failed to get node state: {0}")]
+    FailedToGetNodeState(#[from] ProcessError),
+    #[error("Failed to write config: {0}")]
+    FailedToWriteConfig(io::Error),
+    #[error("Failed to run the editor: {0}")]
+    FailedToRunEditor(io::Error),
+}
+
+/// Config of the app chain along with its DA config, if the DA layer has one
+#[derive(Clone)]
+struct Configs {
+    app_chain: String,
+    config: Table,
+    da_config: Option<JsonValue>,
+}
+
+/// Prints the config and DA config of the app chain
+pub fn show(chain_name: &Option<String>) -> Result<(), ConfigError> {
+    let configs = load_configs(&select_app_chain(chain_name)?)?;
+
+    println!("# {}\n{}", APP_CONFIG_NAME, toml::to_string(&configs.config).map_err(TomlError::from)?);
+    if let Some(da_config) = &configs.da_config {
+        println!(
+            "# {}\n{}",
+            APP_DA_CONFIG_NAME,
+            serde_json::to_string_pretty(da_config).map_err(DaError::FailedToSerializeDaConfig)?
+        );
+    }
+
+    Ok(())
+}
+
+pub fn get(chain_name: &Option<String>, key: &str) -> Result<(), ConfigError> {
+    let configs = load_configs(&select_app_chain(chain_name)?)?;
+
+    let value = match key.strip_prefix(DA_PREFIX) {
+        Some(da_key) => get_json_value(configs.da_config.as_ref(), da_key).map(|value| match value {
+            JsonValue::String(value) => value.clone(),
+            value => value.to_string(),
+        }),
+        None => get_toml_value(&configs.config, key).map(|value| match value {
+            TomlValue::String(value) => value.clone(),
+            value => value.to_string(),
+        }),
+    };
+    println!("{}", value.ok_or(ConfigError::UnknownKey(key.to_string()))?);
+
+    Ok(())
+}
+
+/// Sets the value of the key, keeping the type of the current value
+pub async fn set(chain_name: &Option<String>, key: &str, value: &str) -> Result<(), ConfigError> {
+    let app_chain = select_app_chain(chain_name)?;
+    check_can_change(&app_chain, key).await?;
+
+    let mut configs = load_configs(&app_chain)?;
+    match key.strip_prefix(DA_PREFIX) {
+        Some(da_key) => {
+            let da_config = configs.da_config.as_mut().ok_or(ConfigError::UnknownKey(key.to_string()))?;
+            let current = get_json_value(Some(da_config), da_key).ok_or(ConfigError::UnknownKey(key.to_string()))?;
+            let new_value = parse_json_value(current, value)
+                .ok_or(ConfigError::InvalidValue(key.to_string(), value.to_string()))?;
+            set_json_value(da_config, da_key, new_value);
+        }
+        None => {
+            let current = get_toml_value(&configs.config, key).ok_or(ConfigError::UnknownKey(key.to_string()))?;
+            let new_value = parse_toml_value(current, value)
+                .ok_or(ConfigError::InvalidValue(key.to_string(), value.to_string()))?;
+            set_toml_value(&mut configs.config, key, new_value);
+
+            // the DA config follows the mode of the app chain
+            if key == "mode" {
+                let config = validate(&configs)?;
+                if let Some(JsonValue::Object(da_config)) = configs.da_config.as_mut() {
+                    da_config.insert("mode".to_string(), JsonValue::String(config.mode.da_mode()));
+                }
+            }
+        }
+    }
+
+    validate(&configs)?;
+    save_configs(&configs)?;
+    log::info!("✅ {} of {} set to {}", key, app_chain, value);
+
+    Ok(())
+}
+
+/// Opens the config, or the DA config, in `$VISUAL` or `$EDITOR` and saves it once validated
+pub async fn edit(chain_name: &Option<String>, da: bool) -> Result<(), ConfigError> {
+    let app_chain = select_app_chain(chain_name)?;
+    let previous = load_configs(&app_chain)?;
+    let mut configs = previous.clone();
+
+    let (file_name, content) = match da {
+        true => (
+            APP_DA_CONFIG_NAME,
+            serde_json::to_string_pretty(configs.da_config.as_ref().ok_or(ConfigError::UnknownKey("da".to_string()))?)
+                .map_err(DaError::FailedToSerializeDaConfig)?,
+        ),
+        false => (APP_CONFIG_NAME, toml::to_string(&configs.config).map_err(TomlError::from)?),
+    };
+
+    let edited = edit_in_editor(&app_chain, file_name, &content)?;
+    if edited == content {
+        log::info!("No changes to the config of {}", app_chain);
+        return Ok(());
+    }
+
+    let running = get_running_node(&app_chain).await?.is_some();
+    if da {
+        configs.da_config = Some(serde_json::from_str(&edited).map_err(DaError::FailedToDeserializeDaConfig)?);
+        check_unchanged_json(&previous, &configs, &READ_ONLY_KEYS)
+            .map_err(|key| ConfigError::ReadOnlyKey(key.to_string()))?;
+        if running {
+            check_unchanged_json(&previous, &configs, &IMMUTABLE_KEYS)
+                .map_err(|key| ConfigError::ImmutableWhileRunning(key, app_chain.clone()))?;
+        }
+    } else {
+        configs.config = toml::from_str(&edited).map_err(TomlError::from)?;
+        check_unchanged_toml(&previous, &configs, &READ_ONLY_KEYS)
+            .map_err(|key| ConfigError::ReadOnlyKey(key.to_string()))?;
+        if running {
+            check_unchanged_toml(&previous, &configs, &IMMUTABLE_KEYS)
+                .map_err(|key| ConfigError::ImmutableWhileRunning(key.to_string(), app_chain.clone()))?;
+        }
+    }
+
+    validate(&configs)?;
+    save_configs(&configs)?;
+    log::info!("✅ Config of {} saved", app_chain);
+
+    Ok(())
+}
+
+/// Checks the config and DA config of the app chain
+pub fn validate_app_chain(chain_name: &Option<String>) -> Result<(), ConfigError> {
+    let app_chain = select_app_chain(chain_name)?;
+    validate(&load_configs(&app_chain)?)?;
+    log::info!("✅ Config of {} is valid", app_chain);
+    Ok(())
+}
+
+/// Migrates the config of the app chain, or of all the app chains, to the latest version
+pub fn migrate(chain_name: &Option<String>, dry_run: bool) -> Result<(), ConfigError> {
+    let app_chains = match chain_name {
+        Some(chain_name) => vec![chain_name.to_string()],
+        None => get_app_chain_dirs()?,
+    };
+
+    for app_chain in app_chains {
+        let (config, versions) = migrate_app_config(&app_chain, dry_run)
+            .map_err(|err| ConfigError::FailedToMigrate(app_chain.clone(), err))?;
+        if versions.is_empty() {
+            log::info!("✅ Config of {} is up to date", app_chain);
+            continue;
+        }
+
+        let versions = versions.iter().map(|version| version.to_string()).collect::<Vec<_>>().join(" -> ");
+        if dry_run {
+            log::info!("🔍 Config of {} would be migrated to {}:\n{}", app_chain, versions, config);
+        } else {
+            log::info!("✅ Config of {} migrated to {}", app_chain, versions);
+        }
+    }
+
+    Ok(())
+}
+
+fn select_app_chain(chain_name: &Option<String>) -> Result<String, ConfigError> {
+    match chain_name {
+        Some(chain_name) => Ok(chain_name.to_string()),
+        None => Ok(get_option(CHAIN_NAME, "Select the app chain:", get_apps_list()?)?),
+    }
+}
+
+/// Loads the config, migrated to the latest version and saved first if outdated, along with the
+/// DA config, both with their defaults filled in
+fn load_configs(app_chain: &str) -> Result<Configs, ConfigError> {
+    let (config, _) =
+        migrate_app_config(app_chain, false).map_err(|err| ConfigError::FailedToMigrate(app_chain.to_string(), err))?;
+
+    let da_config_path = get_app_home(app_chain)?.join(APP_DA_CONFIG_NAME);
+    let da_config = match da_config_path.exists() {
+        true => Some(
+            serde_json::from_str(&fs::read_to_string(da_config_path).map_err(DaError::FailedToReadDaConfigFile)?)
+                .map_err(DaError::FailedToDeserializeDaConfig)?,
+        ),
+        false => None,
+    };
+
+    let mut configs = Configs { app_chain: app_chain.to_string(), config, da_config };
+    // fill in the defaults, so that every known key can be read and set
+    let config = validate(&configs)?;
+    configs.config = TomlValue::try_from(&config)
+        .map_err(TomlError::from)?
+        .as_table()
+        .cloned()
+        .ok_or(ConfigError::UnknownKey(APP_CONFIG_NAME.to_string()))?;
+    if let Some(da_config) = configs.da_config.take() {
+        configs.da_config = Some(DAFactory::new_da(&config.da_layer).parse_da_config(da_config)?);
+    }
+
+    Ok(configs)
+}
+
+/// Checks the types of both configs and that the DA layer supports the rollup mode
+fn validate(configs: &Configs) -> Result<AppChainConfig, ConfigError> {
+    let config: AppChainConfig = TomlValue::Table(configs.config.clone()).try_into().map_err(TomlError::from)?;
+    if config.app_chain != configs.app_chain {
+        return Err(ConfigError::ReadOnlyKey("app_chain".to_string()));
+    }
+
+    let da_client = DAFactory::new_da(&config.da_layer);
+    da_client.validate_mode(&config)?;
+    if let Some(da_config) = &configs.da_config {
+        da_client.parse_da_config(da_config.clone())?;
+    }
+
+    Ok(config)
+}
+
+fn save_configs(configs: &Configs) -> Result<(), ConfigError> {
+    let app_home = get_app_home(&configs.app_chain)?;
+    fs::write(app_home.join(APP_CONFIG_NAME), toml::to_string(&configs.config).map_err(TomlError::from)?)
+        .map_err(ConfigError::FailedToWriteConfig)?;
+    if let Some(da_config) = &configs.da_config {
+        fs::write(
+            app_home.join(APP_DA_CONFIG_NAME),
+            serde_json::to_string(da_config).map_err(DaError::FailedToSerializeDaConfig)?,
+        )
+        .map_err(ConfigError::FailedToWriteConfig)?;
+    }
+    Ok(())
+}
+
+async fn check_can_change(app_chain: &str, key: &str) -> Result<(), ConfigError> {
+    if READ_ONLY_KEYS.contains(&key) {
+        return Err(ConfigError::ReadOnlyKey(key.to_string()));
+    }
+    if IMMUTABLE_KEYS.contains(&key) && get_running_node(app_chain).await?.is_some() {
+        return Err(ConfigError::ImmutableWhileRunning(key.to_string(), app_chain.to_string()));
+    }
+    Ok(())
+}
+
+/// Returns the first of the keys whose value differs between the configs
+fn check_unchanged_toml<'a>(previous: &Configs, configs: &Configs, keys: &[&'a str]) -> Result<(), &'a str> {
+    match keys.iter().find(|key| get_toml_value(&previous.config, key) != get_toml_value(&configs.config, key)) {
+        Some(key) => Err(key),
+        None => Ok(()),
+    }
+}
+
+/// Returns the first of the `da.` keys whose value differs between the DA configs
+fn check_unchanged_json(previous: &Configs, configs: &Configs, keys: &[&str]) -> Result<(), String> {
+    let changed = keys.iter().filter_map(|key| key.strip_prefix(DA_PREFIX)).find(|key| {
+        get_json_value(previous.da_config.as_ref(), key) != get_json_value(configs.da_config.as_ref(), key)
+    });
+    match changed {
+        Some(key) => Err(format!("{}{}", DA_PREFIX, key)),
+        None => Ok(()),
+    }
+}
+
+fn edit_in_editor(app_chain: &str, file_name: &str, content: &str) -> Result<String, ConfigError> {
+    let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).unwrap_or("vi".to_string());
+    // the DA config may hold secrets, keep it private under a name that can't be guessed
+    let suffix: String = (0..8).map(|_| rand::thread_rng().sample(Alphanumeric) as char).collect();
+    let path: PathBuf = env::temp_dir().join(format!("madara-{}-{}-{}", app_chain, suffix, file_name));
+    write_private_file(&path, content.as_bytes()).map_err(ConfigError::FailedToRunEditor)?;
+
+    let status = Command::new(&editor).arg(&path).status().map_err(ConfigError::FailedToRunEditor)?;
+    let edited = fs::read_to_string(&path).map_err(ConfigError::FailedToRunEditor);
+    let _ = fs::remove_file(&path);
+    if !status.success() {
+        return Err(ConfigError::FailedToRunEditor(io::Error::other(format!("{} exited with {}", editor, status))));
+    }
+
+    edited
+}
+
+fn get_toml_value<'a>(config: &'a Table, key: &str) -> Option<&'a TomlValue> {
+    let mut keys = key.split('.');
+    let mut value = config.get(keys.next()?)?;
+    for key in keys {
+        value = value.as_table()?.get(key)?;
+    }
+    Some(value)
+}
+
+fn set_toml_value(config: &mut Table, key: &str, new_value: TomlValue) {
+    let (parents, key) = match key.rsplit_once('.') {
+        Some((parents, key)) => (Some(parents), key),
+        None => (None, key),
+    };
+    let mut table = config;
+    for parent in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        match table.get_mut(parent).and_then(TomlValue::as_table_mut) {
+            Some(child) => table = child,
+            None => return,
+        }
+    }
+    table.insert(key.to_string(), new_value);
+}
+
+/// Parses the value with the type of the current value
+fn parse_toml_value(current: &TomlValue, value: &str) -> Option<TomlValue> {
+    match current {
+        TomlValue::String(_) => Some(TomlValue::String(value.to_string())),
+        TomlValue::Integer(_) => value.parse().ok().map(TomlValue::Integer),
+        TomlValue::Float(_) => value.parse().ok().map(TomlValue::Float),
+        TomlValue::Boolean(_) => value.parse().ok().map(TomlValue::Boolean),
+        _ => None,
+    }
+}
+
+fn get_json_value<'a>(config: Option<&'a JsonValue>, key: &str) -> Option<&'a JsonValue> {
+    config?.pointer(&format!("/{}", key.replace('.', "/")))
+}
+
+fn set_json_value(config: &mut JsonValue, key: &str, new_value: JsonValue) {
+    if let Some(value) = config.pointer_mut(&format!("/{}", key.replace('.', "/"))) {
+        *value = new_value;
+    }
+}
+
+/// Parses the value with the type of the current value. Unset optional values take any JSON
+/// value, falling back to a string.
+fn parse_json_value(current: &JsonValue, value: &str) -> Option<JsonValue> {
+    match current {
+        JsonValue::String(_) => Some(JsonValue::String(value.to_string())),
+        JsonValue::Number(_) => value.parse::<serde_json::Number>().ok().map(JsonValue::Number),
+        JsonValue::Bool(_) => value.parse().ok().map(JsonValue::Bool),
+        JsonValue::Null => Some(serde_json::from_str(value).unwrap_or(JsonValue::String(value.to_string()))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn toml_config() -> Table {
+        toml::from_str(
+            r#"
+            app_chain = "test"
+            rpc_port = 9944
+            [logs]
+            max_size_mb = 10
+            max_files = 5
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn get_toml_value_follows_dotted_keys() {
+        let config = toml_config();
+        assert_eq!(get_toml_value(&config, "app_chain"), Some(&TomlValue::String("test".to_string())));
+        assert_eq!(get_toml_value(&config, "logs.max_files"), Some(&TomlValue::Integer(5)));
+        assert!(get_toml_value(&config, "logs").is_some_and(TomlValue::is_table));
+    }
+
+    #[test]
+    fn get_toml_value_of_unknown_key() {
+        let config = toml_config();
+        assert_eq!(get_toml_value(&config, "unknown"), None);
+        assert_eq!(get_toml_value(&config, "logs.unknown"), None);
+        // not a table
+        assert_eq!(get_toml_value(&config, "rpc_port.value"), None);
+    }
+
+    #[test]
+    fn set_toml_value_follows_dotted_keys() {
+        let mut config = toml_config();
+        set_toml_value(&mut config, "rpc_port", TomlValue::Integer(9950));
+        set_toml_value(&mut config, "logs.max_files", TomlValue::Integer(2));
+        assert_eq!(get_toml_value(&config, "rpc_port"), Some(&TomlValue::Integer(9950)));
+        assert_eq!(get_toml_value(&config, "logs.max_files"), Some(&TomlValue::Integer(2)));
+        assert_eq!(get_toml_value(&config, "logs.max_size_mb"), Some(&TomlValue::Integer(10)));
+    }
+
+    #[test]
+    fn set_toml_value_under_missing_table_is_ignored() {
+        let mut config = toml_config();
+        set_toml_value(&mut config, "unknown.key", TomlValue::Integer(1));
+        assert_eq!(config, toml_config());
+    }
+
+    #[test]
+    fn parse_toml_value_keeps_the_type() {
+        assert_eq!(
+            parse_toml_value(&TomlValue::String(String::new()), "42"),
+            Some(TomlValue::String("42".to_string()))
+        );
+        assert_eq!(parse_toml_value(&TomlValue::Integer(0), "42"), Some(TomlValue::Integer(42)));
+        assert_eq!(parse_toml_value(&TomlValue::Float(0.0), "1.5"), Some(TomlValue::Float(1.5)));
+        assert_eq!(parse_toml_value(&TomlValue::Boolean(false), "true"), Some(TomlValue::Boolean(true)));
+    }
+
+    #[test]
+    fn parse_toml_value_rejects_other_types() {
+        assert_eq!(parse_toml_value(&TomlValue::Integer(0), "abc"), None);
+        assert_eq!(parse_toml_value(&TomlValue::Boolean(false), "1"), None);
+        assert_eq!(parse_toml_value(&TomlValue::Table(Table::new()), "abc"), None);
+    }
+
+    #[test]
+    fn get_json_value_follows_dotted_keys() {
+        let config = json!({ "chain_id": 31337, "init_params": { "block_number": 0 } });
+        assert_eq!(get_json_value(Some(&config), "chain_id"), Some(&json!(31337)));
+        assert_eq!(get_json_value(Some(&config), "init_params.block_number"), Some(&json!(0)));
+        assert_eq!(get_json_value(Some(&config), "init_params.unknown"), None);
+        assert_eq!(get_json_value(None, "chain_id"), None);
+    }
+
+    #[test]
+    fn set_json_value_follows_dotted_keys() {
+        let mut config = json!({ "chain_id": 31337, "init_params": { "block_number": 0 } });
+        set_json_value(&mut config, "init_params.block_number", json!(5));
+        set_json_value(&mut config, "unknown.key", json!(1));
+        assert_eq!(config, json!({ "chain_id": 31337, "init_params": { "block_number": 5 } }));
+    }
+
+    #[test]
+    fn parse_json_value_keeps_the_type() {
+        assert_eq!(parse_json_value(&json!(""), "42"), Some(json!("42")));
+        assert_eq!(parse_json_value(&json!(0), "42"), Some(json!(42)));
+        assert_eq!(parse_json_value(&json!(false), "true"), Some(json!(true)));
+        assert_eq!(parse_json_value(&json!(0), "abc"), None);
+        assert_eq!(parse_json_value(&json!({}), "abc"), None);
+    }
+
+    #[test]
+    fn parse_json_value_of_unset_value() {
+        assert_eq!(parse_json_value(&JsonValue::Null, "42"), Some(json!(42)));
+        assert_eq!(parse_json_value(&JsonValue::Null, "[1, 2]"), Some(json!([1, 2])));
+        assert_eq!(parse_json_value(&JsonValue::Null, "abc"), Some(json!("abc")));
+    }
+
+    #[test]
+    fn check_unchanged_json_returns_the_changed_key() {
+        let configs = |local_anvil: bool| Configs {
+            app_chain: "test".to_string(),
+            config: toml_config(),
+            da_config: Some(json!({ "mode": "sovereign", "local_anvil": local_anvil, "chain_id": 31337 })),
+        };
+        let previous = configs(true);
+
+        assert_eq!(check_unchanged_json(&previous, &configs(true), &IMMUTABLE_KEYS), Ok(()));
+        assert_eq!(check_unchanged_json(&previous, &configs(false), &READ_ONLY_KEYS), Ok(()));
+        assert_eq!(
+            check_unchanged_json(&previous, &configs(false), &IMMUTABLE_KEYS),
+            Err("da.local_anvil".to_string())
+        );
+    }
+}
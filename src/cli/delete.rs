@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use inquire::InquireError;
+use thiserror::Error;
+
+use crate::cli::explorer::get_explorer_container_name;
+use crate::cli::list::get_app_chain_dirs;
+use crate::cli::prompt::{get_boolean_input, get_option, CHAIN_NAME, CONFIRM_DELETE};
+use crate::da::da_layers::DAFactory;
+use crate::utils::constants::{APP_CONFIG_NAME, APP_DA_CONFIG_NAME, APP_KEYSTORE_NAME, APP_SECRET_PHRASE};
+use crate::utils::docker::{container_exists, kill_container};
+use crate::utils::errors::ProcessError;
+use crate::utils::paths::{confirm_remove_base_path, get_app_chains_home, get_madara_home};
+use crate::utils::process::get_running_node;
+use crate::utils::toml::regenerate_app_config;
+
+#[derive(Debug, Error)]
+pub enum DeleteError {
+    #[error("Failed to get input: {0}")]
+    FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] std::io::Error),
+    #[error("Failed to get node state: {0}")]
+    FailedToGetNodeState(#[from] ProcessError),
+    #[error("App chain {0} not found")]
+    NotFound(String),
+    #[error("App chain {0} is running, stop it first")]
+    Running(String),
+    #[error("Deletion of {0} cancelled")]
+    Cancelled(String),
+}
+
+/// Removes the app chain, its config, keys, data and explorer. App chains whose config can't be
+/// read are deleted as well, leaving their DA services and external base path alone.
+pub async fn delete(chain_name: &Option<String>, archive_keys: bool, force: bool) -> Result<(), DeleteError> {
+    let app_chain: String = match chain_name {
+        Some(chain_name) => chain_name.to_string(),
+        None => get_option(CHAIN_NAME, "Select the app chain to delete:", get_app_chain_dirs()?)?,
+    };
+
+    let app_home = get_app_chains_home()?.join(&app_chain);
+    if !app_home.join(APP_CONFIG_NAME).is_file() {
+        return Err(DeleteError::NotFound(app_chain));
+    }
+    if get_running_node(&app_chain).await?.is_some() {
+        return Err(DeleteError::Running(app_chain));
+    }
+    let config = match regenerate_app_config(&app_chain) {
+        Ok((config, _)) => Some(config),
+        Err(err) => {
+            log::warn!("Failed to read the config of {}, only its directory is deleted: {}", app_chain, err);
+            None
+        }
+    };
+
+    if !force
+        && !get_boolean_input(
+            CONFIRM_DELETE,
+            &format!("Delete {} along with its keys and data? This can't be undone.", app_chain),
+            Some(false),
+        )?
+    {
+        return Err(DeleteError::Cancelled(app_chain));
+    }
+
+    if archive_keys {
+        let archive = archive_app_chain(&app_chain, &app_home)?;
+        log::info!("🗄️ Config and keys of {} archived in {}", app_chain, archive);
+    }
+
+    // Docker may not be installed, in which case there is no explorer to remove
+    let explorer_container = get_explorer_container_name(&app_chain);
+    if container_exists(&explorer_container).await.unwrap_or(false) {
+        if let Err(err) = kill_container(&explorer_container).await {
+            log::warn!("Failed to remove the explorer of {}: {}", app_chain, err);
+        }
+    }
+
+    if let Some(config) = &config {
+        // the DA services of the app chain are not needed anymore
+        if let Err(err) = DAFactory::new_da(&config.da_layer).teardown(config).await {
+            log::warn!("Failed to stop the DA services of {}: {}", app_chain, err);
+        }
+
+        // the base path may live outside of the app home
+        let base_path = Path::new(&config.base_path);
+        if base_path.exists()
+            && !base_path.starts_with(&app_home)
+            && confirm_remove_base_path(base_path, &app_home, force)?
+        {
+            fs::remove_dir_all(base_path)?;
+        }
+    }
+    fs::remove_dir_all(&app_home)?;
+
+    log::info!("🗑️ App chain {} deleted", app_chain);
+    Ok(())
+}
+
+/// Copies the config and keys of the app chain to `archive` in the Madara home
+fn archive_app_chain(app_chain: &str, app_home: &Path) -> Result<String, std::io::Error> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default();
+    let archive = get_madara_home()?.join("archive").join(format!("{}-{}", app_chain, timestamp));
+    fs::create_dir_all(&archive)?;
+
+    for file_name in [APP_CONFIG_NAME, APP_DA_CONFIG_NAME, APP_KEYSTORE_NAME, APP_SECRET_PHRASE] {
+        let file = app_home.join(file_name);
+        if file.exists() {
+            fs::copy(file, archive.join(file_name))?;
+        }
+    }
+
+    Ok(archive.to_string_lossy().to_string())
+}
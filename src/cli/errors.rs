@@ -0,0 +1,303 @@
+use inquire::InquireError;
+use thiserror::Error;
+
+use crate::binary::provider::BinaryError;
+use crate::cli::config::ConfigError;
+use crate::cli::delete::DeleteError;
+use crate::cli::explorer::ExplorerError;
+use crate::cli::health::HealthCheckError;
+use crate::cli::init::InitError;
+use crate::cli::list::ListError;
+use crate::cli::logs::LogsError;
+use crate::cli::prompt::PromptError;
+use crate::cli::reset::ResetError;
+use crate::cli::restart::RestartError;
+use crate::cli::run::RunError;
+use crate::cli::status::StatusError;
+use crate::cli::stop::StopError;
+use crate::cli::upgrade::UpgradeError;
+use crate::da::avail::AvailError;
+use crate::da::celestia::CelestiaError;
+use crate::da::da_layers::DaError;
+use crate::utils::errors::{DockerError, GithubError, HealthError, MadaraError, ProcessError, TomlError};
+
+/// Top level error of the CLI. Wrapped errors are flattened so that the
+/// variant, and so the exit code, reflects the root cause of the failure.
+#[derive(Debug, Error)]
+pub enum CliError {
+    /// Exit code 1
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Exit code 3
+    #[error(transparent)]
+    Prompt(#[from] PromptError),
+    /// Exit code 3
+    #[error("Failed to get input: {0}")]
+    Input(#[from] InquireError),
+    /// Exit code 10
+    #[error(transparent)]
+    Init(InitError),
+    /// Exit code 11
+    #[error(transparent)]
+    Run(RunError),
+    /// Exit code 12, or 13 when the DA account needs funds
+    #[error(transparent)]
+    Da(#[from] DaError),
+    /// Exit code 14
+    #[error(transparent)]
+    Github(#[from] GithubError),
+    /// Exit code 15
+    #[error(transparent)]
+    Madara(MadaraError),
+    /// Exit code 16
+    #[error(transparent)]
+    Toml(#[from] TomlError),
+    /// Exit code 16
+    #[error(transparent)]
+    Config(ConfigError),
+    /// Exit code 26
+    #[error(transparent)]
+    List(ListError),
+    /// Exit code 20
+    #[error(transparent)]
+    Delete(DeleteError),
+    /// Exit code 21
+    #[error(transparent)]
+    Reset(ResetError),
+    /// Exit code 22
+    #[error(transparent)]
+    Upgrade(UpgradeError),
+    /// Exit code 23
+    #[error(transparent)]
+    Logs(LogsError),
+    /// Exit code 24
+    #[error(transparent)]
+    Health(HealthError),
+    /// Exit code 25
+    #[error(transparent)]
+    Explorer(ExplorerError),
+    /// Exit code 17
+    #[error(transparent)]
+    Docker(#[from] DockerError),
+    /// Exit code 18
+    #[error(transparent)]
+    Process(#[from] ProcessError),
+    /// Exit code 19
+    #[error(transparent)]
+    Binary(#[from] BinaryError),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Io(_) => 1,
+            CliError::Prompt(_) | CliError::Input(_) => 3,
+            CliError::Init(_) => 10,
+            CliError::Run(_) => 11,
+            CliError::Da(DaError::AvailError(AvailError::FaucetFundsNeeded(_)))
+            | CliError::Da(DaError::CelestiaError(CelestiaError::InsufficientBalance { .. })) => 13,
+            CliError::Da(_) => 12,
+            CliError::Github(_) => 14,
+            CliError::Madara(_) => 15,
+            CliError::Toml(_) | CliError::Config(_) => 16,
+            CliError::Docker(_) => 17,
+            CliError::Process(_) => 18,
+            CliError::Binary(_) => 19,
+            CliError::Delete(_) => 20,
+            CliError::Reset(_) => 21,
+            CliError::Upgrade(_) => 22,
+            CliError::Logs(_) => 23,
+            CliError::Health(_) => 24,
+            CliError::Explorer(_) => 25,
+            CliError::List(_) => 26,
+        }
+    }
+
+    /// Human readable report of the error and its causes
+    pub fn report(&self) -> String {
+        let mut report = format!("❌ {}", self);
+        let mut source = std::error::Error::source(self);
+        while let Some(cause) = source {
+            let cause_message = cause.to_string();
+            // most errors already include their cause in the message
+            if !report.contains(&cause_message) {
+                report.push_str(&format!("\n   Caused by: {}", cause_message));
+            }
+            source = cause.source();
+        }
+        report
+    }
+}
+
+impl From<MadaraError> for CliError {
+    fn from(err: MadaraError) -> Self {
+        match err {
+            MadaraError::FailedToStartNode(err) => CliError::Process(err),
+            MadaraError::FailedToRunContainer(err) => CliError::Docker(err),
+            err => CliError::Madara(err),
+        }
+    }
+}
+
+impl From<InitError> for CliError {
+    fn from(err: InitError) -> Self {
+        match err {
+            InitError::FailedToGetInout(err) => CliError::Input(err),
+            InitError::FailedToGetLatestCommitHash(err) => CliError::Github(err),
+            InitError::FailedToGenerateKeypair(err) => CliError::Da(err),
+            err => CliError::Init(err),
+        }
+    }
+}
+
+impl From<RunError> for CliError {
+    fn from(err: RunError) -> Self {
+        match err {
+            RunError::FailedToGetInout(err) => CliError::Input(err),
+            RunError::FailedToGetBinary(err) => CliError::Binary(err),
+            RunError::FailedToStartMadara(err) => CliError::from(err),
+            RunError::FailedToGetAppChains(err) => CliError::Io(err),
+            RunError::FailedWithDaError(err) => CliError::Da(err),
+            RunError::FailedToGetNodeState(err) => CliError::Process(err),
+            RunError::NotReady(err) => CliError::from(err),
+            // DA setup reports its errors through eyre
+            RunError::Other(err) => match err.downcast::<DaError>() {
+                Ok(err) => CliError::Da(err),
+                Err(err) => CliError::Run(RunError::Other(err)),
+            },
+            err => CliError::Run(err),
+        }
+    }
+}
+
+impl From<StopError> for CliError {
+    fn from(err: StopError) -> Self {
+        match err {
+            StopError::FailedToGetInout(err) => CliError::Input(err),
+            StopError::FailedToGetAppChains(err) => CliError::Io(err),
+            StopError::FailedToStopNode(err) => CliError::Process(err),
+        }
+    }
+}
+
+impl From<StatusError> for CliError {
+    fn from(err: StatusError) -> Self {
+        match err {
+            StatusError::FailedToGetAppChains(err) => CliError::Io(err),
+            StatusError::FailedToGetNodeState(err) => CliError::Process(err),
+        }
+    }
+}
+
+impl From<RestartError> for CliError {
+    fn from(err: RestartError) -> Self {
+        match err {
+            RestartError::FailedToGetInout(err) => CliError::Input(err),
+            RestartError::FailedToGetAppChains(err) => CliError::Io(err),
+            RestartError::FailedToStopNode(err) => CliError::Process(err),
+            RestartError::FailedToStartNode(err) => CliError::from(err),
+        }
+    }
+}
+
+impl From<ConfigError> for CliError {
+    fn from(err: ConfigError) -> Self {
+        match err {
+            ConfigError::FailedToGetInout(err) => CliError::Input(err),
+            ConfigError::FailedToGetAppChains(err) => CliError::Io(err),
+            ConfigError::FailedToGetNodeState(err) => CliError::Process(err),
+            err => CliError::Config(err),
+        }
+    }
+}
+
+impl From<ListError> for CliError {
+    fn from(err: ListError) -> Self {
+        match err {
+            ListError::FailedToGetAppChains(err) => CliError::Io(err),
+            err => CliError::List(err),
+        }
+    }
+}
+
+impl From<DeleteError> for CliError {
+    fn from(err: DeleteError) -> Self {
+        match err {
+            DeleteError::FailedToGetInout(err) => CliError::Input(err),
+            DeleteError::FailedToGetAppChains(err) => CliError::Io(err),
+            DeleteError::FailedToGetNodeState(err) => CliError::Process(err),
+            err => CliError::Delete(err),
+        }
+    }
+}
+
+impl From<ResetError> for CliError {
+    fn from(err: ResetError) -> Self {
+        match err {
+            ResetError::FailedToGetInout(err) => CliError::Input(err),
+            ResetError::FailedToGetAppChains(err) => CliError::Io(err),
+            ResetError::FailedToGetNodeState(err) => CliError::Process(err),
+            ResetError::FailedWithDaError(err) => CliError::Da(err),
+            ResetError::FailedToGetBinary(err) => CliError::Binary(err),
+            ResetError::FailedToSetupMadara(err) => CliError::from(err),
+            err => CliError::Reset(err),
+        }
+    }
+}
+
+impl From<UpgradeError> for CliError {
+    fn from(err: UpgradeError) -> Self {
+        match err {
+            UpgradeError::FailedToGetInout(err) => CliError::Input(err),
+            UpgradeError::FailedToGetAppChains(err) => CliError::Io(err),
+            UpgradeError::FailedToGetNodeState(err) => CliError::Process(err),
+            UpgradeError::FailedToResolveVersion(err) => CliError::Github(err),
+            err => CliError::Upgrade(err),
+        }
+    }
+}
+
+impl From<LogsError> for CliError {
+    fn from(err: LogsError) -> Self {
+        match err {
+            LogsError::FailedToGetInout(err) => CliError::Input(err),
+            LogsError::FailedToGetAppChains(err) => CliError::Io(err),
+            LogsError::FailedToGetNodeState(err) => CliError::Process(err),
+            LogsError::FailedToLoadConfig(err) => CliError::Toml(err),
+            LogsError::FailedToReadContainerLogs(err) => CliError::Docker(err),
+            err => CliError::Logs(err),
+        }
+    }
+}
+
+impl From<HealthError> for CliError {
+    fn from(err: HealthError) -> Self {
+        match err {
+            HealthError::FailedToGetNodeState(err) => CliError::Process(err),
+            err => CliError::Health(err),
+        }
+    }
+}
+
+impl From<HealthCheckError> for CliError {
+    fn from(err: HealthCheckError) -> Self {
+        match err {
+            HealthCheckError::FailedToGetInout(err) => CliError::Input(err),
+            HealthCheckError::FailedToGetAppChains(err) => CliError::Io(err),
+            HealthCheckError::Unhealthy(err) => CliError::from(err),
+        }
+    }
+}
+
+impl From<ExplorerError> for CliError {
+    fn from(err: ExplorerError) -> Self {
+        match err {
+            ExplorerError::FailedToGetInout(err) => CliError::Input(err),
+            ExplorerError::FailedToGetAppChains(err) => CliError::Io(err),
+            ExplorerError::FailedToLoadConfig(err) => CliError::Toml(err),
+            ExplorerError::FailedToGetNodeState(err) => CliError::Process(err),
+            ExplorerError::FailedToRunExplorer(err) => CliError::Docker(err),
+            err => CliError::Explorer(err),
+        }
+    }
+}
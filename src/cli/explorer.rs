@@ -3,27 +3,96 @@ use rand::distributions::Alphanumeric;
 use rand::Rng;
 
 use clap::Args;
+use inquire::InquireError;
 use std::collections::HashMap;
+use thiserror::Error;
 
-use crate::utils::docker::{container_exists, kill_container, run_docker_image};
+use crate::cli::list::get_apps_list;
+use crate::cli::prompt::{get_option, CHAIN_NAME};
+use crate::utils::constants::EXPLORER_DEFAULT_PORT;
+use crate::utils::docker::{
+    container_exists, get_published_port, is_container_running, kill_container, run_docker_image,
+};
+use crate::utils::errors::{DockerError, ProcessError, TomlError};
+use crate::utils::ports::{find_free_port, is_port_free};
+use crate::utils::process::get_running_node;
+use crate::utils::toml::regenerate_app_config;
+
+const EXPLORER_IMAGE: &str = "ghcr.io/karnotxyz/starkcompass:latest";
+/// Port the explorer listens on inside its container
+const EXPLORER_CONTAINER_PORT: &str = "4000/tcp";
+
+#[derive(Debug, Error)]
+pub enum ExplorerError {
+    #[error("Failed to get input: {0}")]
+    FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] std::io::Error),
+    #[error("Failed to load the app chain config: {0}")]
+    FailedToLoadConfig(#[from] TomlError),
+    #[error("Failed to get node state: {0}")]
+    FailedToGetNodeState(#[from] ProcessError),
+    #[error("Failed to run the explorer: {0}")]
+    FailedToRunExplorer(#[from] DockerError),
+    #[error("Port {0} is already in use, pick another one with --port")]
+    PortInUse(u16),
+    #[error("No free port from {0} for the explorer")]
+    NoFreePort(u16),
+    #[error("No explorer is running for {0}")]
+    NotRunning(String),
+}
 
 #[derive(Args)]
 pub struct ExplorerOpts {
+    /// App chain to explore
+    #[clap(short, long = "chain-name")]
+    pub name: Option<String>,
     #[clap(long, default_value = "localhost")]
     pub host: String,
+    /// Host port of the explorer, the first free one from 4000 by default
+    #[clap(long)]
+    pub port: Option<u16>,
+    /// RPC the explorer reads from, as reached from its container, the app chain RPC by default
+    #[clap(long)]
+    pub rpc_url: Option<String>,
 }
 
-pub async fn explorer(opts: &ExplorerOpts) {
+pub fn get_explorer_container_name(app_chain: &str) -> String {
+    format!("madara-explorer-{}", app_chain)
+}
+
+/// Runs the explorer of the app chain, replacing the one it may already have. Explorers of other
+/// app chains keep running.
+pub async fn explorer(opts: &ExplorerOpts) -> Result<(), ExplorerError> {
+    let app_chain = select_app_chain(&opts.name, "Select the app chain to explore:")?;
+    let container_name = get_explorer_container_name(&app_chain);
+    let rpc_url = match &opts.rpc_url {
+        Some(rpc_url) => rpc_url.clone(),
+        None => format!("http://host.docker.internal:{}", get_rpc_port(&app_chain).await?),
+    };
+
+    // the previous explorer of the app chain frees its port
+    if container_exists(&container_name).await? {
+        kill_container(&container_name).await?;
+    }
+    let port = match opts.port {
+        Some(port) if !is_port_free(port) => return Err(ExplorerError::PortInUse(port)),
+        Some(port) => port,
+        None => find_free_port(EXPLORER_DEFAULT_PORT, &[]).ok_or(ExplorerError::NoFreePort(EXPLORER_DEFAULT_PORT))?,
+    };
+
     let random_string: String = (0..64).map(|_| rand::thread_rng().sample(Alphanumeric).to_string()).collect();
     let secret_key_base = format!("SECRET_KEY_BASE={}", random_string);
 
+    let rpc_api_host = format!("RPC_API_HOST={}", rpc_url);
+    let testnet_rpc_api_host = format!("TESTNET_RPC_API_HOST={}", rpc_url);
     let host_env = format!("PHX_HOST={}", opts.host);
     let env = vec![
-        "RPC_API_HOST=http://host.docker.internal:9944",
+        rpc_api_host.as_str(),
         "DB_TYPE=sqlite",
         "DISABLE_MAINNET_SYNC=false",
         "DISABLE_TESTNET_SYNC=true",
-        "TESTNET_RPC_API_HOST=http://host.docker.internal:9944",
+        testnet_rpc_api_host.as_str(),
         "DATABASE_PATH=/use/exp.db",
         host_env.as_str(),
         &secret_key_base,
@@ -31,8 +100,8 @@ pub async fn explorer(opts: &ExplorerOpts) {
 
     let mut port_bindings = HashMap::new();
     port_bindings.insert(
-        "4000/tcp".to_string(),
-        Some(vec![PortBinding { host_ip: Some("0.0.0.0".to_string()), host_port: Some("4000".to_string()) }]),
+        EXPLORER_CONTAINER_PORT.to_string(),
+        Some(vec![PortBinding { host_ip: Some("0.0.0.0".to_string()), host_port: Some(port.to_string()) }]),
     );
 
     let host_config = HostConfig {
@@ -41,13 +110,61 @@ pub async fn explorer(opts: &ExplorerOpts) {
         ..Default::default()
     };
 
-    const CONTAINER_NAME: &str = "madara-explorer";
+    run_docker_image(EXPLORER_IMAGE, &container_name, Some(env), None, Some(host_config)).await?;
+    log::info!("🧭 Explorer of {} is running on http://localhost:{}, reading {}", app_chain, port, rpc_url);
 
-    if container_exists(CONTAINER_NAME).await {
-        // TODO: handle error
-        let _ = kill_container(CONTAINER_NAME).await;
+    Ok(())
+}
+
+/// Stops and removes the explorer of the app chain
+pub async fn stop(chain_name: &Option<String>) -> Result<(), ExplorerError> {
+    let app_chain = select_app_chain(chain_name, "Select the app chain:")?;
+    let container_name = get_explorer_container_name(&app_chain);
+    if !container_exists(&container_name).await? {
+        return Err(ExplorerError::NotRunning(app_chain));
     }
 
-    run_docker_image("ghcr.io/karnotxyz/starkcompass:latest", CONTAINER_NAME, Some(env), Some(host_config)).await;
-    log::info!("🧭 Explorer is running on http://localhost:4000");
+    kill_container(&container_name).await?;
+    log::info!("🛑 Explorer of {} stopped", app_chain);
+
+    Ok(())
+}
+
+/// Shows the explorer of the given app chain, or the explorers of all app chains if none is given
+pub async fn status(chain_name: &Option<String>) -> Result<(), ExplorerError> {
+    let app_chains = match chain_name {
+        Some(chain_name) => vec![chain_name.to_string()],
+        None => get_apps_list()?,
+    };
+
+    for app_chain in app_chains {
+        let container_name = get_explorer_container_name(&app_chain);
+        if is_container_running(&container_name).await? {
+            match get_published_port(&container_name, EXPLORER_CONTAINER_PORT).await? {
+                Some(port) => log::info!("🟢 Explorer of {} is running on http://localhost:{}", app_chain, port),
+                None => log::info!("🟢 Explorer of {} is running", app_chain),
+            }
+        } else if container_exists(&container_name).await? {
+            log::info!("🟠 Explorer of {} has exited, see `madara logs -n {} --explorer`", app_chain, app_chain);
+        } else {
+            log::info!("🔴 {} has no explorer", app_chain);
+        }
+    }
+
+    Ok(())
+}
+
+fn select_app_chain(chain_name: &Option<String>, message: &str) -> Result<String, ExplorerError> {
+    match chain_name {
+        Some(chain_name) => Ok(chain_name.to_string()),
+        None => Ok(get_option(CHAIN_NAME, message, get_apps_list()?)?),
+    }
+}
+
+/// RPC port of the running node, or the one of the config if the app chain is stopped
+async fn get_rpc_port(app_chain: &str) -> Result<u16, ExplorerError> {
+    match get_running_node(app_chain).await? {
+        Some(state) => Ok(state.rpc_port),
+        None => Ok(regenerate_app_config(app_chain)?.0.rpc_port),
+    }
 }
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use inquire::InquireError;
+use thiserror::Error;
+
+use crate::cli::list::get_apps_list;
+use crate::cli::prompt::{get_option, CHAIN_NAME};
+use crate::utils::errors::HealthError;
+use crate::utils::health::{get_rpc_url, wait_for_blocks};
+use crate::utils::process::get_running_node;
+
+#[derive(Debug, Error)]
+pub enum HealthCheckError {
+    #[error("Failed to get input: {0}")]
+    FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] std::io::Error),
+    #[error(transparent)]
+    Unhealthy(#[from] HealthError),
+}
+
+/// Checks that the app chain answers on its RPC and Prometheus endpoints and produces blocks
+/// within `timeout` seconds. Fails otherwise, so that it can be used by scripts and healthchecks.
+pub async fn health(chain_name: &Option<String>, timeout: u64) -> Result<(), HealthCheckError> {
+    let app_chain: String = match chain_name {
+        Some(chain_name) => chain_name.to_string(),
+        None => get_option(CHAIN_NAME, "Select the app chain:", get_apps_list()?)?,
+    };
+
+    let state = get_running_node(&app_chain)
+        .await
+        .map_err(HealthError::from)?
+        .ok_or_else(|| HealthError::NotRunning(app_chain.clone()))?;
+    let health = wait_for_blocks(&app_chain, &state, Duration::from_secs(timeout)).await?;
+
+    log::info!("🟢 {} is healthy", app_chain);
+    log::info!("   RPC: {}", get_rpc_url(&state));
+    log::info!("   Chain id: {}", health.chain_id);
+    log::info!("   Block: {}", health.block_number);
+    if let Some(best_block) = health.best_block {
+        log::info!("   Best block (Prometheus): {}", best_block);
+    }
+
+    Ok(())
+}
@@ -1,16 +1,24 @@
-use std::{fs, io};
+use std::path::PathBuf;
+use std::{env, fs, io};
 
 use inquire::InquireError;
 use strum::IntoEnumIterator;
 use thiserror::Error;
 
-use super::prompt::{get_option, get_text_input};
-use crate::app::config::{AppChainConfig, ConfigVersion, RollupMode};
-use crate::da::da_layers::{DAFactory, DALayer};
-use crate::utils::constants::{APP_CONFIG_NAME, MADARA_BRANCH_NAME, MADARA_REPO_NAME, MADARA_REPO_ORG};
+use super::prompt::{get_option, get_text_input, CHAIN_MODE, CHAIN_NAME, DA_LAYER};
+use crate::app::config::{default_madara_repo, AppChainConfig, ConfigVersion, LogsConfig, RollupMode, Runtime};
+use crate::cli::list::get_apps_list;
+use crate::da::da_layers::{DAFactory, DALayer, DaAccountOpts, DaError};
+use crate::utils::constants::{
+    APP_CONFIG_NAME, MADARA_DEFAULT_P2P_PORT, MADARA_DEFAULT_PROMETHEUS_PORT, MADARA_DEFAULT_RPC_PORT,
+};
 use crate::utils::errors::GithubError;
-use crate::utils::github::get_latest_commit_hash;
+use crate::utils::github::normalize_repo_url;
+use crate::utils::madara::resolve_madara_version;
 use crate::utils::paths::{get_app_chains_home, get_app_home};
+use crate::utils::ports::find_free_port;
+use crate::utils::process::get_running_node;
+use crate::utils::toml::regenerate_app_config;
 
 #[derive(Debug, Error)]
 pub enum InitError {
@@ -22,88 +30,132 @@ pub enum InitError {
     FailedToGetLatestCommitHash(#[from] GithubError),
     #[error("Failed to serialize to toml: {0}")]
     FailedToSerializeToToml(#[from] toml::ser::Error),
-    #[error("Failed to generate keypair")]
-    FailedToGenerateKeypair,
+    #[error("Failed to generate keypair: {0}")]
+    FailedToGenerateKeypair(#[from] DaError),
     #[error("Failed to convert string to enum")]
     FailedToGetEnum(#[from] strum::ParseError),
+    #[error("No free port from {0}")]
+    NoFreePort(u16),
 }
 
-pub async fn init(chain_name: &Option<String>, chain_mode: &Option<RollupMode>, da: &Option<DALayer>) {
-    let config = match generate_config(chain_name, chain_mode, da).await {
-        Ok(config) => config,
-        Err(err) => {
-            panic!("Failed to get input: {}", err);
-        }
-    };
-    match write_config(&config) {
-        Ok(config) => config,
-        Err(err) => {
-            panic!("Failed to write config: {}", err);
-        }
-    };
+pub async fn init(
+    chain_name: &Option<String>,
+    chain_mode: &Option<RollupMode>,
+    da: &Option<DALayer>,
+    base_path: &Option<PathBuf>,
+    account: &DaAccountOpts,
+    madara_version: &Option<String>,
+    madara_repo: &Option<String>,
+) -> Result<(), InitError> {
+    let config = generate_config(chain_name, chain_mode, da, base_path, account, madara_version, madara_repo).await?;
+    write_config(&config)?;
 
     log::info!("\n");
     log::info!("✅ New app chain initialised.");
+    log::info!("🔌 Ports: rpc {}, p2p {}, prometheus {}", config.rpc_port, config.p2p_port, config.prometheus_port);
+
+    Ok(())
 }
 
 async fn generate_config(
     chain_name: &Option<String>,
     chain_mode: &Option<RollupMode>,
     da: &Option<DALayer>,
+    base_path: &Option<PathBuf>,
+    account: &DaAccountOpts,
+    madara_version: &Option<String>,
+    madara_repo: &Option<String>,
 ) -> Result<AppChainConfig, InitError> {
     let app_chain: String = match chain_name {
         Some(chain_name) => chain_name.clone(),
-        None => get_text_input("Enter you app chain name:", Some("madara"))?,
+        None => get_text_input(CHAIN_NAME, "Enter you app chain name:", Some("madara"))?,
     };
 
-    let app_chains_home = get_app_chains_home()?;
-    let binding = app_chains_home.join(format!("{}/data", app_chain));
-    let default_base_path = binding.to_str().unwrap_or("madara-data");
+    let base_path = match base_path {
+        Some(base_path) => env::current_dir()?.join(base_path),
+        None => get_app_chains_home()?.join(format!("{}/data", app_chain)),
+    };
 
     let mode: RollupMode = match chain_mode {
         Some(chain_mode) => chain_mode.clone(),
-        None => get_option("Select mode for your app chain:", RollupMode::iter().collect::<Vec<_>>())?,
+        None => get_option(CHAIN_MODE, "Select mode for your app chain:", RollupMode::iter().collect::<Vec<_>>())?,
     };
 
     let da_layer: DALayer = match da {
         Some(da) => da.clone(),
-        None => get_option("Select DA layer for your app chain:", DALayer::iter().collect::<Vec<_>>())?,
+        None => {
+            // only offer the DA layers supporting the mode
+            let da_layers = DALayer::iter()
+                .filter(|da_layer| DAFactory::new_da(da_layer).supported_modes().contains(&mode))
+                .collect::<Vec<_>>();
+            get_option(DA_LAYER, "Select DA layer for your app chain:", da_layers)?
+        }
     };
 
-    let madara_version = get_latest_commit_hash(MADARA_REPO_ORG, MADARA_REPO_NAME, MADARA_BRANCH_NAME).await?;
-    let config_version = ConfigVersion::Version2;
+    let da_client = DAFactory::new_da(&da_layer);
+    account.check_supported(&da_layer, &da_client.supported_account_opts())?;
+
+    let madara_repo = normalize_repo_url(&madara_repo.clone().unwrap_or_else(default_madara_repo))?;
+    let (madara_version, madara_tag) = resolve_madara_version(&madara_repo, madara_version).await?;
+    let config_version = ConfigVersion::latest();
+    let (rpc_port, p2p_port, prometheus_port) = allocate_ports(&app_chain).await?;
 
     log::info!("\n");
 
     let config = AppChainConfig {
         app_chain,
-        base_path: default_base_path.to_string(),
+        base_path: base_path.to_string_lossy().to_string(),
         mode,
         da_layer: da_layer.clone(),
+        runtime: Runtime::default(),
         madara_version,
+        madara_repo,
+        madara_tag,
+        rpc_port,
+        p2p_port,
+        prometheus_port,
         config_version,
+        logs: LogsConfig::default(),
     };
 
-    match DAFactory::new_da(&da_layer).setup_and_generate_keypair(&config) {
-        Ok(_) => (),
-        Err(err) => {
-            log::error!("Failed to generate keypair: {}", err);
-            return Err(InitError::FailedToGenerateKeypair);
-        }
-    };
+    da_client.validate_mode(&config)?;
+    da_client.setup_and_generate_keypair(&config, account).await?;
 
     Ok(config)
 }
 
+/// Picks the first free ports from the defaults of Madara, skipping the ones of the other app
+/// chains so that they can run side by side
+async fn allocate_ports(app_chain: &str) -> Result<(u16, u16, u16), InitError> {
+    let mut reserved = vec![];
+    for other_app_chain in get_apps_list()?.iter().filter(|other_app_chain| *other_app_chain != app_chain) {
+        if let Ok((config, _)) = regenerate_app_config(other_app_chain) {
+            reserved.extend([config.rpc_port, config.p2p_port, config.prometheus_port]);
+        }
+        // the node may have been started with other ports in its flags
+        match get_running_node(other_app_chain).await {
+            Ok(Some(state)) => reserved.extend([state.rpc_port, state.p2p_port, state.prometheus_port]),
+            Ok(None) => (),
+            Err(err) => log::warn!("Skipping the node ports of {}: {}", other_app_chain, err),
+        }
+    }
+
+    let mut ports = vec![];
+    for default_port in [MADARA_DEFAULT_RPC_PORT, MADARA_DEFAULT_P2P_PORT, MADARA_DEFAULT_PROMETHEUS_PORT] {
+        let port = find_free_port(default_port, &reserved).ok_or(InitError::NoFreePort(default_port))?;
+        reserved.push(port);
+        ports.push(port);
+    }
+
+    Ok((ports[0], ports[1], ports[2]))
+}
+
 fn write_config(config: &AppChainConfig) -> Result<(), InitError> {
     let toml = config.to_toml()?;
     let file_path = get_app_home(&config.app_chain)?.join(APP_CONFIG_NAME);
 
-    if let Err(err) = fs::write(file_path, toml) {
-        panic!("Error writing to file: {}", err);
-    } else {
-        log::debug!("Config file saved!");
-    }
+    fs::write(file_path, toml)?;
+    log::debug!("Config file saved!");
 
     Ok(())
 }
@@ -1,19 +1,92 @@
+use std::path::Path;
 use std::{fs, io};
 
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::utils::constants::APP_CONFIG_NAME;
+use crate::utils::madara::get_madara_version_name;
 use crate::utils::paths::get_app_chains_home;
+use crate::utils::process::get_running_node;
 use crate::utils::toml::regenerate_app_config;
 
-pub fn list() {
-    match get_apps_list() {
-        Ok(apps) => {
-            log::info!("App Chain: {:?}", apps);
-        }
-        Err(err) => {
-            panic!("Failed to list: {}", err);
+#[derive(Debug, Clone, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Yaml,
+}
+
+#[derive(Debug, Error)]
+pub enum ListError {
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] io::Error),
+    #[error("Failed to serialize to json: {0}")]
+    FailedToSerializeJson(#[from] serde_json::Error),
+    #[error("Failed to serialize to yaml: {0}")]
+    FailedToSerializeYaml(#[from] serde_yaml::Error),
+}
+
+#[derive(Debug, Serialize)]
+struct AppChainSummary {
+    name: String,
+    mode: String,
+    da_layer: String,
+    madara_version: String,
+    /// Tag of the Madara version, or its short commit
+    madara_version_name: String,
+    config_version: String,
+    base_path: String,
+    /// Size of the base path in bytes
+    base_path_size: u64,
+    status: NodeStatus,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum NodeStatus {
+    Running,
+    Stopped,
+    /// The state of the node can't be read, e.g. it is corrupted or Docker is unreachable
+    Unknown,
+}
+
+impl NodeStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            NodeStatus::Running => "running",
+            NodeStatus::Stopped => "stopped",
+            NodeStatus::Unknown => "unknown",
         }
     }
 }
 
+/// App chain directory whose config can't be loaded
+#[derive(Debug, Serialize)]
+struct InvalidAppChain {
+    name: String,
+    error: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AppChains {
+    app_chains: Vec<AppChainSummary>,
+    invalid: Vec<InvalidAppChain>,
+}
+
+pub async fn list(output: &OutputFormat) -> Result<(), ListError> {
+    let app_chains = get_app_chains().await?;
+
+    match output {
+        OutputFormat::Table => print_table(&app_chains),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&app_chains)?),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&app_chains)?),
+    }
+
+    Ok(())
+}
+
 /// Assumes that all the app configs are saved at "~/.madara/app-chains/{app}/{app}-config.toml"
 /// But return app names after validating the {app}-config.toml
 pub fn get_apps_list() -> Result<Vec<String>, io::Error> {
@@ -46,3 +119,138 @@ pub fn get_apps_list() -> Result<Vec<String>, io::Error> {
 
     Ok(app_names)
 }
+
+/// App chains with a config, including the ones whose config is invalid
+pub fn get_app_chain_dirs() -> Result<Vec<String>, io::Error> {
+    let mut app_chains = vec![];
+    for entry in fs::read_dir(get_app_chains_home()?)? {
+        let entry = entry?;
+        if entry.path().join(APP_CONFIG_NAME).exists() {
+            if let Ok(app_chain) = entry.file_name().into_string() {
+                app_chains.push(app_chain);
+            }
+        }
+    }
+    app_chains.sort();
+    Ok(app_chains)
+}
+
+async fn get_app_chains() -> Result<AppChains, ListError> {
+    let mut app_chains = AppChains { app_chains: vec![], invalid: vec![] };
+
+    let mut names = vec![];
+    for entry in fs::read_dir(get_app_chains_home()?)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    names.sort();
+
+    for name in names {
+        let config = match regenerate_app_config(&name) {
+            Ok((config, _)) => config,
+            Err(err) => {
+                app_chains.invalid.push(InvalidAppChain { name, error: err.to_string() });
+                continue;
+            }
+        };
+
+        let status = match get_running_node(&name).await {
+            Ok(Some(_)) => NodeStatus::Running,
+            Ok(None) => NodeStatus::Stopped,
+            Err(err) => {
+                log::warn!("Failed to get the status of {}: {}", name, err);
+                NodeStatus::Unknown
+            }
+        };
+
+        app_chains.app_chains.push(AppChainSummary {
+            status,
+            name,
+            mode: config.mode.to_string(),
+            da_layer: config.da_layer.to_string(),
+            madara_version_name: get_madara_version_name(&config),
+            madara_version: config.madara_version,
+            config_version: config.config_version.to_string(),
+            base_path_size: get_dir_size(Path::new(&config.base_path)),
+            base_path: config.base_path,
+        });
+    }
+
+    Ok(app_chains)
+}
+
+fn print_table(app_chains: &AppChains) {
+    if app_chains.app_chains.is_empty() {
+        println!("No app chains found. Use `madara init` to create one.");
+    } else {
+        let header = ["NAME", "STATUS", "MODE", "DA LAYER", "MADARA", "CONFIG", "SIZE", "BASE PATH"].map(String::from);
+        let rows: Vec<[String; 8]> = app_chains
+            .app_chains
+            .iter()
+            .map(|app_chain| {
+                [
+                    app_chain.name.clone(),
+                    app_chain.status.as_str().to_string(),
+                    app_chain.mode.clone(),
+                    app_chain.da_layer.clone(),
+                    app_chain.madara_version_name.clone(),
+                    app_chain.config_version.clone(),
+                    format_size(app_chain.base_path_size),
+                    app_chain.base_path.clone(),
+                ]
+            })
+            .collect();
+
+        let mut widths = header.clone().map(|column| column.len());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        for row in std::iter::once(&header).chain(&rows) {
+            let line: Vec<String> =
+                row.iter().zip(widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+            println!("{}", line.join("  ").trim_end());
+        }
+    }
+
+    if !app_chains.invalid.is_empty() {
+        println!("\nInvalid app chains:");
+        for invalid in &app_chains.invalid {
+            println!("  {}: {}", invalid.name, invalid.error);
+        }
+    }
+}
+
+/// Total size of the files in the directory, 0 if it doesn't exist
+fn get_dir_size(path: &Path) -> u64 {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            // symlinks are not followed
+            let metadata = fs::symlink_metadata(entry.path()).ok()?;
+            Some(if metadata.is_dir() { get_dir_size(&entry.path()) } else { metadata.len() })
+        })
+        .sum()
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        _ => format!("{:.1} {}", size, UNITS[unit]),
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Args;
+use inquire::InquireError;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::app::config::Runtime;
+use crate::cli::explorer::get_explorer_container_name;
+use crate::cli::list::get_apps_list;
+use crate::cli::prompt::{get_option, CHAIN_NAME};
+use crate::utils::docker::{container_exists, stream_container_logs};
+use crate::utils::errors::{DockerError, ProcessError, TomlError};
+use crate::utils::logs::{get_line_time, get_log_files, get_node_log_path};
+use crate::utils::madara::get_container_name;
+use crate::utils::process::{get_running_node, NodeHandle};
+use crate::utils::toml::regenerate_app_config;
+
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Error)]
+pub enum LogsError {
+    #[error("Failed to get input: {0}")]
+    FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] io::Error),
+    #[error("Failed to get node state: {0}")]
+    FailedToGetNodeState(#[from] ProcessError),
+    #[error("Failed to load the app chain config: {0}")]
+    FailedToLoadConfig(#[from] TomlError),
+    #[error("Failed to read the logs: {0}")]
+    FailedToReadLogs(io::Error),
+    #[error("Failed to print the logs: {0}")]
+    FailedToPrintLogs(io::Error),
+    #[error("Failed to read the container logs: {0}")]
+    FailedToReadContainerLogs(#[from] DockerError),
+    #[error("No logs found for {0}")]
+    NoLogs(String),
+}
+
+#[derive(Args)]
+pub struct LogsOpts {
+    /// App chain name
+    #[clap(short, long = "chain-name")]
+    pub name: Option<String>,
+    /// Show the logs of the explorer of the app chain instead
+    #[clap(long)]
+    pub explorer: bool,
+    /// Keep printing new lines until the node stops
+    #[clap(short, long)]
+    pub follow: bool,
+    /// Only show the lines logged since, e.g. 10m or 2h
+    #[clap(long, value_parser = humantime::parse_duration)]
+    pub since: Option<Duration>,
+    /// Only show the lines matching the regular expression
+    #[clap(long)]
+    pub grep: Option<Regex>,
+}
+
+/// Lines to print, by time and content
+struct LineFilter {
+    since: Option<SystemTime>,
+    grep: Option<Regex>,
+    /// Whether the last timestamped line was recent enough, lines without a timestamp follow it
+    recent: bool,
+}
+
+impl LineFilter {
+    fn print(&mut self, line: &str) -> Result<(), LogsError> {
+        if let (Some(since), Some(time)) = (self.since, get_line_time(line)) {
+            self.recent = time >= since;
+        }
+        if self.recent && self.grep.as_ref().is_none_or(|grep| grep.is_match(line)) {
+            writeln!(io::stdout(), "{}", line).map_err(LogsError::FailedToPrintLogs)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints the logs of the app chain node, or of the explorer. Native nodes are read from their
+/// log files, nodes in Docker and the explorer from their container.
+pub async fn logs(opts: &LogsOpts) -> Result<(), LogsError> {
+    let since = opts.since.map(|since| SystemTime::now() - since);
+    let mut filter = LineFilter { since, grep: opts.grep.clone(), recent: since.is_none() };
+
+    let result = match opts.explorer {
+        true => print_explorer_logs(&opts.name, opts.follow, &mut filter).await,
+        false => print_node_logs(&opts.name, opts.follow, &mut filter).await,
+    };
+    match result {
+        // e.g. piped to `head`
+        Err(LogsError::FailedToPrintLogs(err)) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+async fn print_node_logs(chain_name: &Option<String>, follow: bool, filter: &mut LineFilter) -> Result<(), LogsError> {
+    let app_chain = select_app_chain(chain_name)?;
+    // the container is kept after the node stops or crashes, so its logs can still be read
+    let container_name = get_container_name(&app_chain);
+    if regenerate_app_config(&app_chain)?.0.runtime == Runtime::Docker && container_exists(&container_name).await? {
+        return print_container_logs(&container_name, follow, filter).await;
+    }
+
+    match get_running_node(&app_chain).await?.map(|state| state.handle) {
+        Some(NodeHandle::Container(container_name)) => print_container_logs(&container_name, follow, filter).await,
+        _ => print_file_logs(&app_chain, follow, filter).await,
+    }
+}
+
+async fn print_explorer_logs(
+    chain_name: &Option<String>,
+    follow: bool,
+    filter: &mut LineFilter,
+) -> Result<(), LogsError> {
+    let app_chain = select_app_chain(chain_name)?;
+    print_container_logs(&get_explorer_container_name(&app_chain), follow, filter).await
+}
+
+fn select_app_chain(chain_name: &Option<String>) -> Result<String, LogsError> {
+    match chain_name {
+        Some(chain_name) => Ok(chain_name.to_string()),
+        None => Ok(get_option(CHAIN_NAME, "Select the app chain:", get_apps_list()?)?),
+    }
+}
+
+async fn print_container_logs(container_name: &str, follow: bool, filter: &mut LineFilter) -> Result<(), LogsError> {
+    if !container_exists(container_name).await? {
+        return Err(LogsError::NoLogs(container_name.to_string()));
+    }
+
+    // Docker filters by time itself
+    let since = filter.since.and_then(|since| since.duration_since(UNIX_EPOCH).ok());
+    let since = since.map(|since| since.as_secs() as i64).unwrap_or_default();
+    filter.since = None;
+    filter.recent = true;
+
+    let mut printed = Ok(());
+    stream_container_logs(container_name, follow, since, |line| {
+        printed = filter.print(line);
+        printed.is_ok()
+    })
+    .await?;
+    printed
+}
+
+async fn print_file_logs(app_chain: &str, follow: bool, filter: &mut LineFilter) -> Result<(), LogsError> {
+    let log_path = get_node_log_path(app_chain)?;
+    let log_files = get_log_files(&log_path);
+    if log_files.is_empty() {
+        return Err(LogsError::NoLogs(app_chain.to_string()));
+    }
+
+    let mut offset = 0;
+    for log_file in &log_files {
+        offset = print_new_lines(log_file, 0, filter)?;
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut inode = fs::metadata(&log_path).map(|metadata| metadata.ino()).unwrap_or_default();
+    loop {
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+        let running = get_running_node(app_chain).await?.is_some();
+
+        match fs::metadata(&log_path) {
+            // the log was rotated, finish the previous file before reading the new one
+            Ok(metadata) if metadata.ino() != inode => {
+                if let Some(rotated_path) = get_log_files(&log_path).iter().rev().nth(1) {
+                    print_new_lines(rotated_path, offset, filter)?;
+                }
+                inode = metadata.ino();
+                offset = print_new_lines(&log_path, 0, filter)?;
+            }
+            Ok(_) => offset = print_new_lines(&log_path, offset, filter)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(LogsError::FailedToReadLogs(err)),
+        }
+
+        if !running {
+            return Ok(());
+        }
+    }
+}
+
+/// Prints the complete lines written after `offset` and returns the offset of the first line
+/// which isn't complete yet
+fn print_new_lines(path: &Path, offset: u64, filter: &mut LineFilter) -> Result<u64, LogsError> {
+    let mut content = Vec::new();
+    let mut file = File::open(path).map_err(LogsError::FailedToReadLogs)?;
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.read_to_end(&mut content))
+        .map_err(LogsError::FailedToReadLogs)?;
+
+    let complete = content.iter().rposition(|byte| *byte == b'\n').map_or(0, |end| end + 1);
+    for line in String::from_utf8_lossy(&content[..complete]).lines() {
+        filter.print(line)?;
+    }
+
+    Ok(offset + complete as u64)
+}
@@ -7,3 +7,25 @@ pub mod list;
 pub mod run;
 
 pub mod explorer;
+
+pub mod stop;
+
+pub mod status;
+
+pub mod restart;
+
+pub mod config;
+
+pub mod delete;
+
+pub mod reset;
+
+pub mod upgrade;
+
+pub mod versions;
+
+pub mod logs;
+
+pub mod health;
+
+pub mod errors;
@@ -1,18 +1,103 @@
 use std::fmt::Display;
+use std::fs;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
-use inquire::{Confirm, CustomType, InquireError, Select};
+use inquire::{Confirm, CustomType, InquireError, Password, PasswordDisplayMode, Select};
+use thiserror::Error;
 
-pub fn get_text_input(message: &str, default: Option<&str>) -> Result<String, InquireError> {
+// Keys looked up in the answers file when prompting
+pub const CHAIN_NAME: &str = "chain_name";
+pub const CHAIN_MODE: &str = "chain_mode";
+pub const DA_LAYER: &str = "da_layer";
+pub const ETHEREUM_NODE: &str = "ethereum_node";
+pub const ETHEREUM_PROVIDER: &str = "ethereum_provider";
+pub const ETHEREUM_CHAIN_ID: &str = "ethereum_chain_id";
+pub const ANVIL_PORT: &str = "anvil_port";
+pub const AVAIL_ACCOUNT: &str = "avail_account";
+pub const AVAIL_SEED: &str = "avail_seed";
+pub const ETHEREUM_VERIFIER: &str = "ethereum_verifier";
+pub const CONFIRM_DELETE: &str = "confirm_delete";
+pub const CONFIRM_DELETE_BASE_PATH: &str = "confirm_delete_base_path";
+pub const CONFIRM_RESET: &str = "confirm_reset";
+pub const ETHEREUM_ACCOUNT: &str = "ethereum_account";
+pub const ETHEREUM_PRIVATE_KEY: &str = "ethereum_private_key";
+pub const ETHEREUM_KEYSTORE: &str = "ethereum_keystore";
+pub const ETHEREUM_KEYSTORE_PASSWORD: &str = "ethereum_keystore_password";
+pub const KEYSTORE_PASSWORD: &str = "keystore_password";
+pub const CELESTIA_AUTH: &str = "celestia_auth";
+pub const CELESTIA_PROVIDER: &str = "celestia_provider";
+pub const CELESTIA_AUTH_TOKEN: &str = "celestia_auth_token";
+pub const CELESTIA_NAMESPACE: &str = "celestia_namespace";
+
+#[derive(Debug, Error)]
+pub enum PromptError {
+    #[error("No value provided for `{0}`, pass it as a flag or in the answers file")]
+    MissingAnswer(String),
+    #[error("Invalid value `{1}` for `{0}`")]
+    InvalidAnswer(String, String),
+    #[error("Failed to read answers file: {0}")]
+    FailedToReadAnswersFile(#[from] std::io::Error),
+    #[error("Failed to parse answers file: {0}")]
+    FailedToParseAnswersFile(#[from] toml::de::Error),
+    #[error("Prompt mode is already configured")]
+    AlreadyConfigured,
+}
+
+#[derive(Default)]
+struct PromptConfig {
+    non_interactive: bool,
+    answers: toml::Table,
+}
+
+static PROMPT_CONFIG: OnceLock<PromptConfig> = OnceLock::new();
+
+/// Configures how prompts are answered. In non-interactive mode, prompts are
+/// answered from the answers file, falling back to their default value.
+/// Passing an answers file implies non-interactive mode.
+pub fn configure_prompts(non_interactive: bool, answers_file: &Option<PathBuf>) -> Result<(), PromptError> {
+    let answers = match answers_file {
+        Some(path) => toml::from_str::<toml::Table>(&fs::read_to_string(path)?)?,
+        None => toml::Table::new(),
+    };
+    let config = PromptConfig { non_interactive: non_interactive || answers_file.is_some(), answers };
+    PROMPT_CONFIG.set(config).map_err(|_| PromptError::AlreadyConfigured)
+}
+
+pub fn is_non_interactive() -> bool {
+    PROMPT_CONFIG.get().map(|config| config.non_interactive).unwrap_or(false)
+}
+
+fn get_answer(key: &str) -> Option<String> {
+    PROMPT_CONFIG.get().and_then(|config| config.answers.get(key)).map(|value| match value {
+        toml::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    })
+}
+
+fn prompt_error(err: PromptError) -> InquireError {
+    InquireError::Custom(Box::new(err))
+}
+
+pub fn get_text_input(key: &str, message: &str, default: Option<&str>) -> Result<String, InquireError> {
     let default = default.map(|s| s.to_string());
-    get_custom_input::<String>(message, default, None)
+    get_custom_input::<String>(key, message, default, None)
 }
 
 pub fn get_custom_input<T: Clone + FromStr + Display>(
+    key: &str,
     message: &str,
     default: Option<T>,
     help_message: Option<&str>,
 ) -> Result<T, InquireError> {
+    if let Some(answer) = get_answer(key) {
+        return T::from_str(&answer).map_err(|_| prompt_error(PromptError::InvalidAnswer(key.to_string(), answer)));
+    }
+    if is_non_interactive() {
+        return default.ok_or_else(|| prompt_error(PromptError::MissingAnswer(key.to_string())));
+    }
+
     let mut prompt = CustomType::new(message);
     if let Some(default) = default {
         prompt = prompt.with_default(default);
@@ -23,14 +108,57 @@ pub fn get_custom_input<T: Clone + FromStr + Display>(
     prompt.prompt()
 }
 
-pub fn get_option<T: Display>(message: &str, options: Vec<T>) -> Result<T, InquireError> {
+pub fn get_option<T: Display>(key: &str, message: &str, options: Vec<T>) -> Result<T, InquireError> {
+    if let Some(answer) = get_answer(key) {
+        return options
+            .into_iter()
+            .find(|option| option.to_string().eq_ignore_ascii_case(&answer))
+            .ok_or_else(|| prompt_error(PromptError::InvalidAnswer(key.to_string(), answer)));
+    }
+    if is_non_interactive() {
+        return Err(prompt_error(PromptError::MissingAnswer(key.to_string())));
+    }
+
     Select::new(message, options).prompt()
 }
 
-pub fn get_boolean_input(message: &str, default: Option<bool>) -> Result<bool, InquireError> {
+pub fn get_boolean_input(key: &str, message: &str, default: Option<bool>) -> Result<bool, InquireError> {
+    if let Some(answer) = get_answer(key) {
+        return bool::from_str(&answer).map_err(|_| prompt_error(PromptError::InvalidAnswer(key.to_string(), answer)));
+    }
+    if is_non_interactive() {
+        return default.ok_or_else(|| prompt_error(PromptError::MissingAnswer(key.to_string())));
+    }
+
     let mut prompt = Confirm::new(message);
     if let Some(default) = default {
         prompt = prompt.with_default(default);
     }
     prompt.prompt()
 }
+
+pub fn get_password_input(key: &str, message: &str) -> Result<String, InquireError> {
+    if let Some(answer) = get_answer(key) {
+        return Ok(answer);
+    }
+    if is_non_interactive() {
+        return Err(prompt_error(PromptError::MissingAnswer(key.to_string())));
+    }
+
+    Password::new(message).with_display_mode(PasswordDisplayMode::Masked).without_confirmation().prompt()
+}
+
+/// Like `get_password_input`, but asks for the password twice
+pub fn get_new_password_input(key: &str, message: &str) -> Result<String, InquireError> {
+    if let Some(answer) = get_answer(key) {
+        return Ok(answer);
+    }
+    if is_non_interactive() {
+        return Err(prompt_error(PromptError::MissingAnswer(key.to_string())));
+    }
+
+    Password::new(message)
+        .with_display_mode(PasswordDisplayMode::Masked)
+        .with_custom_confirmation_message("Confirm the password:")
+        .prompt()
+}
@@ -0,0 +1,82 @@
+use std::fs;
+use std::path::Path;
+
+use inquire::InquireError;
+use thiserror::Error;
+
+use crate::app::config::Runtime;
+use crate::binary::provider::{BinaryError, BinaryFactory, BinarySource};
+use crate::cli::list::get_apps_list;
+use crate::cli::prompt::{get_boolean_input, get_option, CHAIN_NAME, CONFIRM_RESET};
+use crate::da::da_layers::{DAFactory, DaError};
+use crate::utils::errors::{MadaraError, ProcessError, TomlError};
+use crate::utils::madara;
+use crate::utils::paths::{confirm_remove_base_path, get_app_chains_home};
+use crate::utils::process::get_running_node;
+use crate::utils::toml::regenerate_app_config;
+
+#[derive(Debug, Error)]
+pub enum ResetError {
+    #[error("Failed to get input: {0}")]
+    FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] std::io::Error),
+    #[error("Failed to get node state: {0}")]
+    FailedToGetNodeState(#[from] ProcessError),
+    #[error("Failed to read config of {0}: {1}")]
+    FailedToReadConfig(String, TomlError),
+    #[error("Failed to reset DA layer: {0}")]
+    FailedWithDaError(#[from] DaError),
+    #[error("Failed to get Madara binary: {0}")]
+    FailedToGetBinary(#[from] BinaryError),
+    #[error("Failed to setup Madara: {0}")]
+    FailedToSetupMadara(#[from] MadaraError),
+    #[error("App chain {0} is running, stop it first")]
+    Running(String),
+    #[error("Reset of {0} cancelled")]
+    Cancelled(String),
+}
+
+/// Wipes the chain data of the app chain, keeping its config and keys, and sets it up again
+pub async fn reset(chain_name: &Option<String>, source: &BinarySource, force: bool) -> Result<(), ResetError> {
+    let app_chain: String = match chain_name {
+        Some(chain_name) => chain_name.to_string(),
+        None => get_option(CHAIN_NAME, "Select the app chain to reset:", get_apps_list()?)?,
+    };
+
+    if get_running_node(&app_chain).await?.is_some() {
+        return Err(ResetError::Running(app_chain));
+    }
+    let (config, _) =
+        regenerate_app_config(&app_chain).map_err(|err| ResetError::FailedToReadConfig(app_chain.clone(), err))?;
+
+    if !force
+        && !get_boolean_input(
+            CONFIRM_RESET,
+            &format!("Wipe the chain data of {} in {}? Config and keys are kept.", app_chain, config.base_path),
+            Some(false),
+        )?
+    {
+        return Err(ResetError::Cancelled(app_chain));
+    }
+
+    let base_path = Path::new(&config.base_path);
+    if base_path.exists() {
+        if !confirm_remove_base_path(base_path, &get_app_chains_home()?.join(&app_chain), force)? {
+            return Err(ResetError::Cancelled(app_chain));
+        }
+        fs::remove_dir_all(base_path)?;
+    }
+    DAFactory::new_da(&config.da_layer).reset(&config).await?;
+
+    match config.runtime {
+        Runtime::Native => {
+            let binary = BinaryFactory::new_provider(source).get_binary(&config).await?;
+            madara::setup_madara(&config, &binary)?;
+        }
+        Runtime::Docker => madara::setup_madara_in_docker(&config).await?,
+    }
+
+    log::info!("♻️ App chain {} reset", app_chain);
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use inquire::InquireError;
+use thiserror::Error;
+
+use crate::binary::provider::BinarySource;
+use crate::cli::list::get_apps_list;
+use crate::cli::prompt::{get_option, CHAIN_NAME};
+use crate::cli::run::{start_app_chain, wait_until_ready, RunError};
+use crate::cli::stop::stop_node;
+use crate::utils::errors::ProcessError;
+use crate::utils::process::{get_running_node, NodeState};
+
+#[derive(Debug, Error)]
+pub enum RestartError {
+    #[error("Failed to get input: {0}")]
+    FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] std::io::Error),
+    #[error("Failed to stop node: {0}")]
+    FailedToStopNode(#[from] ProcessError),
+    #[error("Failed to start node: {0}")]
+    FailedToStartNode(#[from] RunError),
+}
+
+pub async fn restart(
+    chain_name: &Option<String>,
+    madara_flags: &[String],
+    source: &Option<BinarySource>,
+    timeout: u64,
+    health_timeout: u64,
+) -> Result<(), RestartError> {
+    let (app_chain, state) = restart_app_chain(chain_name, madara_flags, source, Duration::from_secs(timeout)).await?;
+    wait_until_ready(&app_chain, &state, health_timeout).await?;
+    Ok(())
+}
+
+async fn restart_app_chain(
+    chain_name: &Option<String>,
+    madara_flags: &[String],
+    source: &Option<BinarySource>,
+    timeout: Duration,
+) -> Result<(String, NodeState), RestartError> {
+    let app_chain: String = match chain_name {
+        Some(chain_name) => chain_name.to_string(),
+        None => {
+            let app_chains_list = get_apps_list()?;
+            get_option(CHAIN_NAME, "Select the app chain:", app_chains_list)?
+        }
+    };
+
+    // Reuse the flags and binary of the running node unless new ones are passed
+    let mut flags = madara_flags.to_vec();
+    let mut source = source.clone();
+    if let Some(state) = get_running_node(&app_chain).await? {
+        if flags.is_empty() {
+            flags = state.madara_flags;
+        }
+        if source.is_none() {
+            source = state.binary.map(BinarySource::Local);
+        }
+        stop_node(&app_chain, timeout).await?;
+    }
+
+    Ok(start_app_chain(&Some(app_chain), &flags, &source.unwrap_or(BinarySource::Release), &None).await?)
+}
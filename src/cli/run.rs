@@ -1,64 +1,159 @@
+use std::time::Duration;
+
 use inquire::InquireError;
 use thiserror::Error;
 
+use crate::app::config::Runtime;
+use crate::binary::provider::{BinaryError, BinaryFactory, BinarySource};
 use crate::cli::list::get_apps_list;
-use crate::cli::prompt::get_option;
+use crate::cli::prompt::{get_option, CHAIN_NAME};
 use crate::da::da_layers::{DAFactory, DaError};
-use crate::utils::errors::MadaraError;
+use crate::utils::errors::{HealthError, MadaraError, ProcessError, TomlError};
+use crate::utils::health::{get_rpc_url, wait_for_blocks};
+use crate::utils::keystore::remove_unlocked_da_config;
 use crate::utils::madara;
-use crate::utils::toml::regenerate_app_config;
+use crate::utils::ports::is_port_free;
+use crate::utils::process::{get_node_ports, get_running_node, NodeState};
+use crate::utils::toml::{regenerate_app_config, save_app_config};
 
 #[derive(Debug, Error)]
 pub enum RunError {
     #[error("Failed to get input: {0}")]
     FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get Madara binary: {0}")]
+    FailedToGetBinary(#[from] BinaryError),
     #[error("Failed to start madara: {0}")]
     FailedToStartMadara(#[from] MadaraError),
     #[error("Failed to get app chains: {0}")]
     FailedToGetAppChains(#[from] std::io::Error),
-    #[error("Failed to regenerate config: {0}")]
-    FailedToRegenerateConfig(String),
+    #[error("Failed to regenerate config of {0}: {1}")]
+    FailedToRegenerateConfig(String, TomlError),
     #[error("Failed with DA error: {0}")]
     FailedWithDaError(#[from] DaError),
+    #[error("App chain {0} is already running")]
+    AlreadyRunning(String),
+    #[error("Failed to get node state: {0}")]
+    FailedToGetNodeState(#[from] ProcessError),
+    #[error("Port {0} is used by app chain {1}, change it with `madara config set`")]
+    PortUsedByAppChain(u16, String),
+    #[error("Port {0} is already in use, change it with `madara config set`")]
+    PortInUse(u16),
+    #[error(transparent)]
+    NotReady(#[from] HealthError),
     #[error(transparent)]
     Other(#[from] eyre::Error),
 }
 
-pub async fn run(chain_name: &Option<String>, madara_flags: &[String]) {
-    match start_app_chain(chain_name, madara_flags).await {
-        Ok(_) => {
-            log::info!("Madara setup successful");
-        }
-        Err(err) => {
-            log::error!("Failed to setup Madara: {}", err);
-        }
+pub async fn run(
+    chain_name: &Option<String>,
+    madara_flags: &[String],
+    source: &BinarySource,
+    runtime: &Option<Runtime>,
+    health_timeout: u64,
+) -> Result<(), RunError> {
+    let (app_chain, state) = start_app_chain(chain_name, madara_flags, source, runtime).await?;
+    wait_until_ready(&app_chain, &state, health_timeout).await?;
+    Ok(())
+}
+
+/// Waits for the started node to produce blocks, for up to `timeout` seconds. Skipped if the
+/// timeout is 0.
+pub async fn wait_until_ready(app_chain: &str, state: &NodeState, timeout: u64) -> Result<(), RunError> {
+    if timeout == 0 {
+        return Ok(());
     }
+
+    log::info!("⏳ Waiting for {} to produce blocks", app_chain);
+    let health = wait_for_blocks(app_chain, state, Duration::from_secs(timeout)).await?;
+    log::info!(
+        "✅ App chain {} is ready at {} (chain id {}, block {})",
+        app_chain,
+        get_rpc_url(state),
+        health.chain_id,
+        health.block_number
+    );
+    Ok(())
 }
 
-async fn start_app_chain(chain_name: &Option<String>, madara_flags: &[String]) -> Result<(), RunError> {
+/// Starts the app chain in the background and returns its name and the state of its node
+pub async fn start_app_chain(
+    chain_name: &Option<String>,
+    madara_flags: &[String],
+    source: &BinarySource,
+    runtime: &Option<Runtime>,
+) -> Result<(String, NodeState), RunError> {
     let app_chain: String = match chain_name {
         Some(chain_name) => chain_name.to_string(),
         None => {
             let app_chains_list = get_apps_list()?;
-            get_option("Select the app chain:", app_chains_list)?
+            get_option(CHAIN_NAME, "Select the app chain:", app_chains_list)?
         }
     };
 
-    let (config, _) = match regenerate_app_config(&app_chain) {
-        Ok((config, valid)) => (config, valid),
-        Err(err) => {
-            log::error!("Failed to fetch the required app chain: {}", err);
-            return Err(RunError::FailedToRegenerateConfig(app_chain.to_string()));
+    let (mut config, _) =
+        regenerate_app_config(&app_chain).map_err(|err| RunError::FailedToRegenerateConfig(app_chain.clone(), err))?;
+
+    if let Some(state) = get_running_node(&app_chain).await? {
+        log::info!("App chain {} is already running with {}. Use `madara restart` instead.", app_chain, state.handle);
+        return Err(RunError::AlreadyRunning(app_chain));
+    }
+
+    // the runtime is remembered for the next runs
+    if let Some(runtime) = runtime {
+        if *runtime != config.runtime {
+            config.runtime = runtime.clone();
+            save_app_config(&config).map_err(|err| RunError::FailedToRegenerateConfig(app_chain.clone(), err))?;
         }
-    };
+    }
+
+    check_ports(&app_chain, get_node_ports(&config, madara_flags)).await?;
 
-    madara::clone_madara_and_build_repo(&config)?;
+    // only native nodes need a binary on the host
+    let binary = match config.runtime {
+        Runtime::Native => Some(BinaryFactory::new_provider(source).get_binary(&config).await?),
+        Runtime::Docker => None,
+    };
 
     let da_factory = DAFactory::new_da(&config.da_layer);
-    da_factory.confirm_minimum_balance(&config)?;
+    da_factory.validate_mode(&config)?;
+    da_factory.confirm_minimum_balance(&config).await?;
     da_factory.setup(&config).await?;
+    let da_config_path = da_factory.unlock_da_config(&config).await?;
 
-    madara::setup_and_run_madara(config, madara_flags)?;
+    let state = match binary {
+        Some(binary) => madara::setup_and_run_madara(config, &binary, &da_config_path, madara_flags),
+        None => madara::setup_and_run_madara_in_docker(config, &da_config_path, madara_flags).await,
+    };
+    // the unlocked DA config is otherwise removed when the node is stopped
+    let state = state.inspect_err(|_| {
+        let _ = remove_unlocked_da_config(&app_chain);
+    })?;
+    log::info!("🚀 App chain {} started in the background with {}", app_chain, state.handle);
+    if let Some(log_file) = &state.log_file {
+        log::info!("📜 Node logs are written to {}", log_file.display());
+    }
+    log::info!("📜 Follow them with `madara logs -n {} --follow`", app_chain);
 
-    Ok(())
+    Ok((app_chain, state))
+}
+
+/// Fails if another app chain runs on the ports of the node, or if another process listens
+/// on them
+async fn check_ports(app_chain: &str, ports: (u16, u16, u16)) -> Result<(), RunError> {
+    let (rpc_port, p2p_port, prometheus_port) = ports;
+    let ports = [rpc_port, p2p_port, prometheus_port];
+
+    for other_app_chain in get_apps_list()?.iter().filter(|other_app_chain| *other_app_chain != app_chain) {
+        if let Some(state) = get_running_node(other_app_chain).await? {
+            let other_ports = [state.rpc_port, state.p2p_port, state.prometheus_port];
+            if let Some(port) = ports.iter().find(|port| other_ports.contains(port)) {
+                return Err(RunError::PortUsedByAppChain(*port, other_app_chain.clone()));
+            }
+        }
+    }
+
+    match ports.iter().find(|port| !is_port_free(**port)) {
+        Some(port) => Err(RunError::PortInUse(*port)),
+        None => Ok(()),
+    }
 }
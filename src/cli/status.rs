@@ -0,0 +1,49 @@
+use thiserror::Error;
+
+use crate::cli::list::get_apps_list;
+use crate::utils::errors::ProcessError;
+use crate::utils::process::{format_duration, get_running_node};
+
+#[derive(Debug, Error)]
+pub enum StatusError {
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] std::io::Error),
+    #[error("Failed to get node state: {0}")]
+    FailedToGetNodeState(#[from] ProcessError),
+}
+
+/// Shows the status of the given app chain, or of all app chains if none is given
+pub async fn status(chain_name: &Option<String>) -> Result<(), StatusError> {
+    let app_chains = match chain_name {
+        Some(chain_name) => vec![chain_name.to_string()],
+        None => get_apps_list()?,
+    };
+
+    if app_chains.is_empty() {
+        log::info!("No app chains found. Use `madara init` to create one.");
+    }
+
+    for app_chain in app_chains {
+        match get_running_node(&app_chain).await? {
+            Some(state) => {
+                log::info!("🟢 {} is running ({})", app_chain, state.handle);
+                log::info!("   Uptime: {}", format_duration(state.uptime()));
+                log::info!("   DA layer: {}", state.da_layer);
+                log::info!(
+                    "   Ports: rpc {}, p2p {}, prometheus {}",
+                    state.rpc_port,
+                    state.p2p_port,
+                    state.prometheus_port
+                );
+                if let Some(log_file) = &state.log_file {
+                    log::info!("   Logs: {}", log_file.display());
+                }
+            }
+            None => {
+                log::info!("🔴 {} is stopped", app_chain);
+            }
+        }
+    }
+
+    Ok(())
+}
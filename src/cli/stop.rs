@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use inquire::InquireError;
+use thiserror::Error;
+
+use crate::cli::list::get_apps_list;
+use crate::cli::prompt::{get_option, CHAIN_NAME};
+use crate::utils::docker::stop_container;
+use crate::utils::errors::ProcessError;
+use crate::utils::keystore::remove_unlocked_da_config;
+use crate::utils::process::{get_running_node, remove_node_state, stop_process, NodeHandle};
+
+#[derive(Debug, Error)]
+pub enum StopError {
+    #[error("Failed to get input: {0}")]
+    FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] std::io::Error),
+    #[error("Failed to stop node: {0}")]
+    FailedToStopNode(#[from] ProcessError),
+}
+
+pub async fn stop(chain_name: &Option<String>, timeout: u64) -> Result<(), StopError> {
+    let app_chain = stop_app_chain(chain_name, Duration::from_secs(timeout)).await?;
+    log::info!("🛑 App chain {} stopped", app_chain);
+    Ok(())
+}
+
+async fn stop_app_chain(chain_name: &Option<String>, timeout: Duration) -> Result<String, StopError> {
+    let app_chain: String = match chain_name {
+        Some(chain_name) => chain_name.to_string(),
+        None => {
+            let mut running_app_chains = vec![];
+            for app in get_apps_list()? {
+                if let Ok(Some(_)) = get_running_node(&app).await {
+                    running_app_chains.push(app);
+                }
+            }
+            get_option(CHAIN_NAME, "Select the app chain to stop:", running_app_chains)?
+        }
+    };
+
+    stop_node(&app_chain, timeout).await?;
+
+    Ok(app_chain)
+}
+
+/// Stops the node of the app chain and removes its state file
+pub async fn stop_node(app_chain: &str, timeout: Duration) -> Result<(), ProcessError> {
+    let state = match get_running_node(app_chain).await? {
+        Some(state) => state,
+        None => return Err(ProcessError::NotRunning(app_chain.to_string())),
+    };
+
+    log::info!("Stopping {} ({})", app_chain, state.handle);
+    match &state.handle {
+        NodeHandle::Process(pid) => stop_process(*pid, state.process_start_time, timeout).await?,
+        NodeHandle::Container(name) => stop_container(name, timeout.as_secs()).await?,
+    }
+    remove_node_state(app_chain)?;
+    if let Err(err) = remove_unlocked_da_config(app_chain) {
+        log::warn!("Failed to remove the unlocked DA config of {}: {}", app_chain, err);
+    }
+
+    Ok(())
+}
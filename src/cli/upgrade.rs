@@ -0,0 +1,68 @@
+use inquire::InquireError;
+use thiserror::Error;
+
+use crate::cli::list::get_apps_list;
+use crate::cli::prompt::{get_option, CHAIN_NAME};
+use crate::utils::errors::{GithubError, ProcessError, TomlError};
+use crate::utils::github::normalize_repo_url;
+use crate::utils::madara::{get_madara_version_name, resolve_madara_version};
+use crate::utils::process::get_running_node;
+use crate::utils::toml::{regenerate_app_config, save_app_config};
+
+#[derive(Debug, Error)]
+pub enum UpgradeError {
+    #[error("Failed to get input: {0}")]
+    FailedToGetInout(#[from] InquireError),
+    #[error("Failed to get app chains: {0}")]
+    FailedToGetAppChains(#[from] std::io::Error),
+    #[error("Failed to get node state: {0}")]
+    FailedToGetNodeState(#[from] ProcessError),
+    #[error("Failed to read config of {0}: {1}")]
+    FailedToReadConfig(String, TomlError),
+    #[error("Failed to resolve Madara version: {0}")]
+    FailedToResolveVersion(#[from] GithubError),
+    #[error("Failed to save config: {0}")]
+    FailedToSaveConfig(TomlError),
+    #[error("App chain {0} is running, stop it first")]
+    Running(String),
+}
+
+/// Moves the app chain to another Madara version, resolved like at `init`. The chain data is
+/// kept, `madara reset` wipes it if the new version can't read it.
+pub async fn upgrade(
+    chain_name: &Option<String>,
+    madara_version: &Option<String>,
+    madara_repo: &Option<String>,
+) -> Result<(), UpgradeError> {
+    let app_chain: String = match chain_name {
+        Some(chain_name) => chain_name.to_string(),
+        None => get_option(CHAIN_NAME, "Select the app chain to upgrade:", get_apps_list()?)?,
+    };
+
+    if get_running_node(&app_chain).await?.is_some() {
+        return Err(UpgradeError::Running(app_chain));
+    }
+    let (mut config, _) =
+        regenerate_app_config(&app_chain).map_err(|err| UpgradeError::FailedToReadConfig(app_chain.clone(), err))?;
+
+    let madara_repo = normalize_repo_url(&madara_repo.clone().unwrap_or_else(|| config.madara_repo.clone()))?;
+    let (commit, tag) = resolve_madara_version(&madara_repo, madara_version).await?;
+    if commit == config.madara_version && madara_repo == config.madara_repo {
+        log::info!("App chain {} already uses Madara {}", app_chain, get_madara_version_name(&config));
+        return Ok(());
+    }
+
+    let previous_version = get_madara_version_name(&config);
+    config.madara_version = commit;
+    config.madara_tag = tag;
+    config.madara_repo = madara_repo;
+    save_app_config(&config).map_err(UpgradeError::FailedToSaveConfig)?;
+
+    log::info!(
+        "⬆️ App chain {} upgraded from Madara {} to {}",
+        app_chain,
+        previous_version,
+        get_madara_version_name(&config)
+    );
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use crate::app::config::default_madara_repo;
+use crate::utils::errors::GithubError;
+use crate::utils::github::{get_local_tags, get_tags, parse_github_repo};
+use crate::utils::madara::get_local_clone;
+
+/// Lists the tags of the Madara repository, which can be passed to `--madara-version`
+pub async fn versions(madara_repo: &Option<String>) -> Result<(), GithubError> {
+    let madara_repo = madara_repo.clone().unwrap_or_else(default_madara_repo);
+    let tags = match get_local_clone(&madara_repo)? {
+        Some(path) => get_local_tags(&path)?,
+        None => {
+            let (org, repo) = parse_github_repo(&madara_repo)?;
+            get_tags(&org, &repo).await?
+        }
+    };
+    if tags.is_empty() {
+        println!("No tags found in {}, use a branch or a commit instead", madara_repo);
+        return Ok(());
+    }
+
+    let width = tags.iter().map(|tag| tag.name.len()).max().unwrap_or_default();
+    for tag in tags {
+        // short commit, like git
+        println!("{:<width$}  {}", tag.name, &tag.commit.sha[..7.min(tag.commit.sha.len())], width = width);
+    }
+
+    Ok(())
+}
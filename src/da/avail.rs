@@ -1,14 +1,22 @@
 use async_trait::async_trait;
 use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use crate::app::config::AppChainConfig;
-use crate::cli::prompt::get_boolean_input;
+use crate::app::config::{AppChainConfig, RollupMode};
+use ethers::providers::{Provider, ProviderError, Ws};
 use hex::encode;
 use serde::{Deserialize, Serialize};
+use sp_core::crypto::Ss58Codec;
+use sp_core::hashing::{blake2_128, twox_128};
 use sp_core::{sr25519, Pair};
 use thiserror::Error;
 
-use crate::da::da_layers::{DaClient, DaError};
+use crate::cli::prompt::{get_option, get_password_input, AVAIL_ACCOUNT, AVAIL_SEED};
+use crate::da::da_layers::{DaAccountOpts, DaClient, DaError};
+use crate::utils::keystore::{
+    get_keystore_password, get_keystore_path, read_sr25519_keystore, write_sr25519_keystore, write_unlocked_da_config,
+};
 
 pub struct AvailClient;
 
@@ -16,66 +24,137 @@ pub struct AvailClient;
 pub struct AvailConfig {
     pub ws_provider: String,
     pub mode: String,
+    /// Left empty when the key is in the keystore, a seed set here takes precedence
     pub seed: String,
     pub app_id: u32,
     pub address: String,
+    /// Minimum free balance (in the smallest unit) needed to start the app chain
+    #[serde(default = "default_min_balance")]
+    pub min_balance: u128,
+    /// How long to wait for the address to be funded, 0 fails right away
+    #[serde(default = "default_funding_timeout_secs")]
+    pub funding_timeout_secs: u64,
 }
 
 #[derive(Error, Debug)]
 pub enum AvailError {
     #[error("Failed to serialize config: {0}")]
     FailedToSerializeConfig(#[from] serde_json::Error),
-    #[error("Faucet funds needed for DA to be submitted")]
-    FaucetFundsNeeded,
+    #[error("Faucet funds needed for DA to be submitted, current balance is {} AVL", format_balance(*.0))]
+    FaucetFundsNeeded(u128),
+    #[error("Invalid Avail address: {0}")]
+    InvalidAddress(String),
+    #[error("Failed to query balance: {0}")]
+    FailedToQueryBalance(#[from] ProviderError),
+    #[error("Invalid account data: {0}")]
+    InvalidAccountData(String),
+    #[error("Invalid seed or secret phrase")]
+    InvalidSeed,
+    #[error("Failed to read the mnemonic file: {0}")]
+    FailedToReadMnemonicFile(std::io::Error),
 }
 
 const AVAIL_DOCS: &str = "https://docs.availproject.org/about/faucet/";
+const AVAIL_DECIMALS: u32 = 18;
+const BALANCE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+const GENERATE_ACCOUNT_OPTION: &str = "Generate a new account";
+const IMPORT_ACCOUNT_OPTION: &str = "Import a seed or secret phrase";
+
+fn default_min_balance() -> u128 {
+    10u128.pow(AVAIL_DECIMALS)
+}
+
+fn default_funding_timeout_secs() -> u64 {
+    300
+}
 
 #[async_trait]
 impl DaClient for AvailClient {
-    fn setup_and_generate_keypair(&self, config: &AppChainConfig) -> Result<(), DaError> {
+    fn supported_modes(&self) -> Vec<RollupMode> {
+        vec![RollupMode::Sovereign]
+    }
+
+    fn parse_da_config(&self, da_config: serde_json::Value) -> Result<serde_json::Value, DaError> {
+        let avail_config: AvailConfig =
+            serde_json::from_value(da_config).map_err(DaError::FailedToDeserializeDaConfig)?;
+        serde_json::to_value(avail_config).map_err(DaError::FailedToSerializeDaConfig)
+    }
+
+    fn supported_account_opts(&self) -> Vec<&'static str> {
+        vec!["--da-seed", "--da-mnemonic-file"]
+    }
+
+    async fn setup_and_generate_keypair(
+        &self,
+        config: &AppChainConfig,
+        account: &DaAccountOpts,
+    ) -> Result<(), DaError> {
         let file_path = self.get_da_config_path(config)?;
         let file_path_str = file_path.to_string_lossy().to_string();
-        let (pair, phrase, seed) = <sr25519::Pair as Pair>::generate_with_phrase(None);
-        let seed_str = format!("0x{}", encode(seed.as_ref()));
-
-        if let Err(err) = fs::write(file_path, phrase) {
-            panic!("Error writing to file: {}", err);
-        } else {
-            log::info!("🔑 Secret phrase stored in app home: {}", file_path_str);
-            log::info!("💧 Avail address: {}", pair.public());
+        let (pair, seed, imported) = get_account(account)?;
+        let address = pair.public().to_string();
+
+        let password = get_keystore_password(true)?;
+        write_sr25519_keystore(&config.app_chain, seed.as_ref(), &address, &password)?;
+        log::info!("🔑 Avail key encrypted in the keystore: {}", get_keystore_path(&config.app_chain)?.display());
+        log::info!("💧 Avail address: {}", address);
+        if !imported {
             log::info!(
                 "📕 Please fund your Avail address to be able to submit blobs to the goldberg network. Docs: {}.",
                 AVAIL_DOCS
             );
-            log::info!(
-                "🛠️ If you want to use a custom Avail address, please enter your seed and address in the config file: {}.",
-                file_path_str
-            );
         }
 
-        generate_config(file_path_str.as_str(), &seed_str, pair.public().to_string().as_str())?;
+        generate_config(file_path_str.as_str(), &config.mode, "", address.as_str())?;
 
         Ok(())
     }
 
-    fn confirm_minimum_balance(&self, config: &AppChainConfig) -> Result<(), DaError> {
-        let avail_config_path = self.get_da_config_path(config)?;
-        let avail_config: AvailConfig = serde_json::from_str(
-            fs::read_to_string(avail_config_path).map_err(DaError::FailedToReadDaConfigFile)?.as_str(),
-        )
-        .map_err(DaError::FailedToDeserializeDaConfig)?;
-        match get_boolean_input(
-            format!(
-                "Have you funded your Avail address {} using the faucet? Docs: {}",
-                avail_config.address, AVAIL_DOCS
-            )
-            .as_str(),
-            Some(true),
-        )? {
-            true => Ok(()),
-            false => Err(DaError::AvailError(AvailError::FaucetFundsNeeded)),
+    async fn confirm_minimum_balance(&self, config: &AppChainConfig) -> Result<(), DaError> {
+        let avail_config = self.read_config(config)?;
+
+        let mut balance = get_free_balance(&avail_config).await?;
+        if balance >= avail_config.min_balance {
+            log::info!("💰 Avail balance: {} AVL", format_balance(balance));
+            return Ok(());
         }
+
+        log::info!(
+            "💸 Your Avail address {} needs {} AVL more to submit blobs. Please fund it using the faucet. Docs: {}",
+            avail_config.address,
+            format_balance(avail_config.min_balance - balance),
+            AVAIL_DOCS
+        );
+
+        let timeout = Duration::from_secs(avail_config.funding_timeout_secs);
+        let mut waited = Duration::ZERO;
+        while waited < timeout {
+            log::info!("⏳ Waiting for funds, {}s left", (timeout - waited).as_secs());
+            tokio::time::sleep(BALANCE_POLL_INTERVAL).await;
+            waited += BALANCE_POLL_INTERVAL;
+
+            balance = get_free_balance(&avail_config).await?;
+            if balance >= avail_config.min_balance {
+                log::info!("💰 Avail balance: {} AVL", format_balance(balance));
+                return Ok(());
+            }
+        }
+
+        Err(DaError::AvailError(AvailError::FaucetFundsNeeded(balance)))
+    }
+
+    async fn unlock_da_config(&self, config: &AppChainConfig) -> Result<PathBuf, DaError> {
+        let mut avail_config = self.read_config(config)?;
+        if !avail_config.seed.is_empty() {
+            return self.get_da_config_path(config);
+        }
+
+        let password = get_keystore_password(false)?;
+        avail_config.seed = format!("0x{}", encode(read_sr25519_keystore(&config.app_chain, &password)?));
+        let avail_config = serde_json::to_value(avail_config).map_err(DaError::FailedToSerializeDaConfig)?;
+
+        Ok(write_unlocked_da_config(&config.app_chain, &avail_config)?)
     }
 
     async fn setup(&self, _config: &AppChainConfig) -> eyre::Result<()> {
@@ -83,13 +162,57 @@ impl DaClient for AvailClient {
     }
 }
 
-fn generate_config(da_config_path: &str, seed: &str, address: &str) -> Result<(), DaError> {
+impl AvailClient {
+    fn read_config(&self, config: &AppChainConfig) -> Result<AvailConfig, DaError> {
+        let avail_config_path = self.get_da_config_path(config)?;
+        serde_json::from_str(fs::read_to_string(avail_config_path).map_err(DaError::FailedToReadDaConfigFile)?.as_str())
+            .map_err(DaError::FailedToDeserializeDaConfig)
+    }
+}
+
+/// Imports the account given with `--da-seed` or `--da-mnemonic-file`, or asks whether to import
+/// one or generate a new one. Returns the key pair, its seed and whether it was imported.
+fn get_account(account: &DaAccountOpts) -> Result<(sr25519::Pair, [u8; 32], bool), DaError> {
+    let secret = match (&account.seed, &account.mnemonic_file) {
+        (Some(seed), _) => Some(seed.clone()),
+        (None, Some(mnemonic_file)) => {
+            Some(fs::read_to_string(mnemonic_file).map_err(AvailError::FailedToReadMnemonicFile)?)
+        }
+        (None, None) => match get_option(
+            AVAIL_ACCOUNT,
+            "Which account should submit the blobs?",
+            vec![GENERATE_ACCOUNT_OPTION, IMPORT_ACCOUNT_OPTION],
+        )? {
+            IMPORT_ACCOUNT_OPTION => {
+                Some(get_password_input(AVAIL_SEED, "Enter the seed or secret phrase of the account:")?)
+            }
+            _ => None,
+        },
+    };
+
+    match secret {
+        Some(secret) => {
+            let (pair, seed) = <sr25519::Pair as Pair>::from_string_with_seed(secret.trim(), None)
+                .map_err(|_| AvailError::InvalidSeed)?;
+            // the keystore holds the seed, which keys derived with soft junctions don't have
+            Ok((pair, seed.ok_or(AvailError::InvalidSeed)?, true))
+        }
+        None => {
+            let (pair, _, seed) = <sr25519::Pair as Pair>::generate_with_phrase(None);
+            Ok((pair, seed, false))
+        }
+    }
+}
+
+fn generate_config(da_config_path: &str, mode: &RollupMode, seed: &str, address: &str) -> Result<(), DaError> {
     let avail_config = AvailConfig {
         ws_provider: "wss://karnot-rpc.avail.tools:443/ws".to_string(),
-        mode: "sovereign".to_string(),
+        mode: mode.da_mode(),
         seed: seed.to_string(),
         app_id: 0,
         address: address.to_string(),
+        min_balance: default_min_balance(),
+        funding_timeout_secs: default_funding_timeout_secs(),
     };
 
     fs::write(da_config_path, serde_json::to_string(&avail_config).map_err(DaError::FailedToSerializeDaConfig)?)
@@ -97,3 +220,47 @@ fn generate_config(da_config_path: &str, seed: &str, address: &str) -> Result<()
 
     Ok(())
 }
+
+/// Reads the free balance of the address from the `System.Account` storage
+async fn get_free_balance(avail_config: &AvailConfig) -> Result<u128, AvailError> {
+    let account = sr25519::Public::from_ss58check(&avail_config.address)
+        .map_err(|_| AvailError::InvalidAddress(avail_config.address.clone()))?;
+    let account_id: &[u8] = account.as_ref();
+
+    // storage map keys are hashed with `Blake2_128Concat`
+    let mut storage_key = [twox_128(b"System"), twox_128(b"Account")].concat();
+    storage_key.extend(blake2_128(account_id));
+    storage_key.extend(account_id);
+
+    let provider = Provider::<Ws>::connect(avail_config.ws_provider.as_str()).await?;
+    let account_info: Option<String> =
+        provider.request("state_getStorage", [format!("0x{}", encode(storage_key))]).await?;
+
+    // accounts never funded have no storage entry
+    let account_info = match account_info {
+        Some(account_info) => account_info,
+        None => return Ok(0),
+    };
+
+    // SCALE encoded `AccountInfo`: nonce, consumers, providers and sufficients (u32 each)
+    // are followed by the `AccountData`, starting with the free balance (u128)
+    let bytes = hex::decode(account_info.trim_start_matches("0x"))
+        .map_err(|_| AvailError::InvalidAccountData(account_info.clone()))?;
+    let free = bytes
+        .get(16..32)
+        .and_then(|free| <[u8; 16]>::try_from(free).ok())
+        .ok_or(AvailError::InvalidAccountData(account_info.clone()))?;
+
+    Ok(u128::from_le_bytes(free))
+}
+
+fn format_balance(balance: u128) -> String {
+    let unit = 10u128.pow(AVAIL_DECIMALS);
+    let fraction = format!("{:018}", balance % unit);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{}", balance / unit)
+    } else {
+        format!("{}.{}", balance / unit, fraction)
+    }
+}
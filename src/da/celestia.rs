@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bollard::models::{HostConfig, PortBinding};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::app::config::{AppChainConfig, RollupMode};
+use crate::cli::list::get_app_chain_dirs;
+use crate::cli::prompt::{
+    get_custom_input, get_option, get_text_input, CELESTIA_AUTH, CELESTIA_AUTH_TOKEN, CELESTIA_NAMESPACE,
+    CELESTIA_PROVIDER,
+};
+use crate::da::da_layers::{DaAccountOpts, DaClient, DaError};
+use crate::utils::constants::APP_DA_CONFIG_NAME;
+use crate::utils::docker::{
+    container_exists, exec_in_container, is_container_running, kill_container, run_docker_image,
+};
+use crate::utils::errors::DockerError;
+use crate::utils::keystore::{
+    get_keystore_password, get_keystore_path, read_celestia_keystore, write_celestia_keystore, write_unlocked_da_config,
+};
+use crate::utils::paths::get_app_home;
+
+pub struct CelestiaClient;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CelestiaConfig {
+    pub http_provider: String,
+    pub ws_provider: String,
+    /// Namespace the blobs of the app chain are submitted to
+    pub nid: String,
+    /// Left unset when the token is in the keystore, a token set here takes precedence. The token
+    /// of the local devnet is fetched from it on every run.
+    pub auth_token: Option<String>,
+    pub mode: String,
+    /// Minimum balance (in utia) the light node account needs before starting the app chain
+    #[serde(default = "default_min_balance")]
+    pub min_balance: u64,
+    /// How long to wait for the account to be funded, 0 fails right away
+    #[serde(default = "default_funding_timeout_secs")]
+    pub funding_timeout_secs: u64,
+    /// Whether the light node is a `celestia-devnet` container managed by the CLI
+    #[serde(default)]
+    pub local_devnet: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum CelestiaError {
+    #[error("Failed to run celestia-devnet: {0}")]
+    FailedToRunDevnet(#[from] DockerError),
+    #[error("Failed to get auth token from celestia-devnet")]
+    FailedToGetAuthToken,
+    #[error("Failed to query the light node: {0}")]
+    FailedToQueryNode(#[from] reqwest::Error),
+    #[error("Light node returned an error: {0}")]
+    NodeError(String),
+    #[error("Insufficient balance, {balance} utia available but {min_balance} utia needed")]
+    InsufficientBalance { balance: u64, min_balance: u64 },
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct Balance {
+    amount: String,
+}
+
+const CELESTIA_DOCS: &str = "https://docs.celestia.org/developers/node-tutorial";
+const CELESTIA_DEVNET_IMAGE: &str = "ghcr.io/rollkit/local-celestia-devnet:v0.12.7";
+const CELESTIA_DEVNET_CONTAINER_NAME: &str = "celestia-devnet";
+const CELESTIA_DEVNET_PROVIDER: &str = "http://localhost:26658";
+const CELESTIA_DEVNET_PORTS: [u16; 5] = [26650, 26657, 26658, 26659, 9090];
+const DEVNET_STARTUP_ATTEMPTS: u32 = 30;
+const BALANCE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+const LOCAL_DEVNET_OPTION: &str = "Generate using a local celestia-devnet";
+const IMPORT_OPTION: &str = "Import from my light node";
+
+fn default_min_balance() -> u64 {
+    1_000_000
+}
+
+fn default_funding_timeout_secs() -> u64 {
+    300
+}
+
+#[async_trait]
+impl DaClient for CelestiaClient {
+    fn supported_modes(&self) -> Vec<RollupMode> {
+        vec![RollupMode::Sovereign]
+    }
+
+    fn parse_da_config(&self, da_config: serde_json::Value) -> Result<serde_json::Value, DaError> {
+        let celestia_config: CelestiaConfig =
+            serde_json::from_value(da_config).map_err(DaError::FailedToDeserializeDaConfig)?;
+        serde_json::to_value(celestia_config).map_err(DaError::FailedToSerializeDaConfig)
+    }
+
+    async fn setup_and_generate_keypair(
+        &self,
+        config: &AppChainConfig,
+        _account: &DaAccountOpts,
+    ) -> Result<(), DaError> {
+        let file_path = self.get_da_config_path(config)?;
+
+        let local_devnet = get_option(
+            CELESTIA_AUTH,
+            "How do you want to get the auth token of the Celestia light node?",
+            vec![LOCAL_DEVNET_OPTION, IMPORT_OPTION],
+        )? == LOCAL_DEVNET_OPTION;
+
+        let http_provider = if local_devnet {
+            CELESTIA_DEVNET_PROVIDER.to_string()
+        } else {
+            let http_provider = get_text_input(
+                CELESTIA_PROVIDER,
+                "Enter the RPC endpoint of your light node:",
+                Some(CELESTIA_DEVNET_PROVIDER),
+            )?;
+            let auth_token = get_custom_input::<String>(
+                CELESTIA_AUTH_TOKEN,
+                "Enter the auth token of your light node:",
+                None,
+                Some("Generate one with `celestia light auth write`"),
+            )?;
+
+            let password = get_keystore_password(true)?;
+            write_celestia_keystore(&config.app_chain, &auth_token, &password)?;
+            log::info!(
+                "🔑 Celestia auth token encrypted in the keystore: {}",
+                get_keystore_path(&config.app_chain)?.display()
+            );
+            http_provider
+        };
+
+        let random_namespace: String = (0..10).map(|_| rand::thread_rng().sample(Alphanumeric) as char).collect();
+        let nid =
+            get_text_input(CELESTIA_NAMESPACE, "Enter the namespace of your app chain:", Some(&random_namespace))?;
+
+        let celestia_config = CelestiaConfig {
+            ws_provider: http_provider.replacen("http", "ws", 1),
+            http_provider,
+            nid,
+            auth_token: None,
+            mode: config.mode.da_mode(),
+            min_balance: default_min_balance(),
+            funding_timeout_secs: default_funding_timeout_secs(),
+            local_devnet,
+        };
+
+        fs::write(file_path, serde_json::to_string(&celestia_config).map_err(DaError::FailedToSerializeDaConfig)?)
+            .map_err(DaError::FailedToWriteDaConfigToFile)?;
+
+        log::info!("🌌 Celestia namespace: {}", celestia_config.nid);
+        if !local_devnet {
+            log::info!(
+                "📕 Please fund the account of your light node to be able to submit blobs. Docs: {}",
+                CELESTIA_DOCS
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn confirm_minimum_balance(&self, config: &AppChainConfig) -> Result<(), DaError> {
+        let mut celestia_config = self.read_config(config)?;
+        if celestia_config.local_devnet {
+            start_devnet().await?;
+        }
+        celestia_config.auth_token = Some(self.get_auth_token(config, &celestia_config).await?);
+
+        let mut balance = get_balance(&celestia_config).await?;
+        if balance >= celestia_config.min_balance {
+            log::info!("💰 Celestia light node balance: {} utia", balance);
+            return Ok(());
+        }
+
+        log::info!(
+            "💸 The light node account needs {} utia more to submit blobs. Please fund it. Docs: {}",
+            celestia_config.min_balance - balance,
+            CELESTIA_DOCS
+        );
+
+        let timeout = Duration::from_secs(celestia_config.funding_timeout_secs);
+        let mut waited = Duration::ZERO;
+        while waited < timeout {
+            log::info!("⏳ Waiting for funds, {}s left", (timeout - waited).as_secs());
+            tokio::time::sleep(BALANCE_POLL_INTERVAL).await;
+            waited += BALANCE_POLL_INTERVAL;
+
+            balance = get_balance(&celestia_config).await?;
+            if balance >= celestia_config.min_balance {
+                log::info!("💰 Celestia light node balance: {} utia", balance);
+                return Ok(());
+            }
+        }
+
+        Err(CelestiaError::InsufficientBalance { balance, min_balance: celestia_config.min_balance }.into())
+    }
+
+    async fn unlock_da_config(&self, config: &AppChainConfig) -> Result<PathBuf, DaError> {
+        let mut celestia_config = self.read_config(config)?;
+        if celestia_config.auth_token.is_some() && !celestia_config.local_devnet {
+            return self.get_da_config_path(config);
+        }
+
+        celestia_config.auth_token = Some(self.get_auth_token(config, &celestia_config).await?);
+        let celestia_config = serde_json::to_value(celestia_config).map_err(DaError::FailedToSerializeDaConfig)?;
+
+        Ok(write_unlocked_da_config(&config.app_chain, &celestia_config)?)
+    }
+
+    async fn setup(&self, _config: &AppChainConfig) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    /// A new devnet is started on the next run, without the blobs of the previous chain state,
+    /// unless another app chain uses it
+    async fn reset(&self, config: &AppChainConfig) -> Result<(), DaError> {
+        self.teardown(config).await
+    }
+
+    /// celestia-devnet is shared by the app chains and binds fixed ports, so it's removed once the
+    /// last app chain using it is
+    async fn teardown(&self, config: &AppChainConfig) -> Result<(), DaError> {
+        let celestia_config = self.read_config(config)?;
+        if !celestia_config.local_devnet
+            || !container_exists(CELESTIA_DEVNET_CONTAINER_NAME).await.map_err(CelestiaError::from)?
+        {
+            return Ok(());
+        }
+
+        match get_other_devnet_app_chains(&config.app_chain)?.first() {
+            Some(app_chain) => log::info!("🌌 celestia-devnet is kept, {} uses it too", app_chain),
+            None => {
+                kill_container(CELESTIA_DEVNET_CONTAINER_NAME).await.map_err(CelestiaError::from)?;
+                log::info!("🌌 celestia-devnet removed");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CelestiaClient {
+    fn read_config(&self, config: &AppChainConfig) -> Result<CelestiaConfig, DaError> {
+        let celestia_config_path = self.get_da_config_path(config)?;
+        serde_json::from_str(
+            fs::read_to_string(celestia_config_path).map_err(DaError::FailedToReadDaConfigFile)?.as_str(),
+        )
+        .map_err(DaError::FailedToDeserializeDaConfig)
+    }
+
+    /// Auth token of the light node, issued by the local devnet, set in the config or else
+    /// decrypted from the keystore
+    async fn get_auth_token(
+        &self,
+        config: &AppChainConfig,
+        celestia_config: &CelestiaConfig,
+    ) -> Result<String, DaError> {
+        if celestia_config.local_devnet {
+            return Ok(get_devnet_auth_token().await?);
+        }
+        if let Some(auth_token) = &celestia_config.auth_token {
+            return Ok(auth_token.clone());
+        }
+
+        let password = get_keystore_password(false)?;
+        Ok(read_celestia_keystore(&config.app_chain, &password)?)
+    }
+}
+
+/// Other app chains whose DA layer is the local devnet
+fn get_other_devnet_app_chains(app_chain: &str) -> Result<Vec<String>, DaError> {
+    let mut app_chains = vec![];
+    for other in get_app_chain_dirs().map_err(DaError::FailedToReadAppHome)? {
+        let da_config_path = get_app_home(&other).map_err(DaError::FailedToReadAppHome)?.join(APP_DA_CONFIG_NAME);
+        // app chains on other DA layers or with an unreadable DA config don't use it
+        if other != app_chain && fs::read_to_string(da_config_path).is_ok_and(|da_config| uses_devnet(&da_config)) {
+            app_chains.push(other);
+        }
+    }
+    Ok(app_chains)
+}
+
+fn uses_devnet(da_config: &str) -> bool {
+    serde_json::from_str::<CelestiaConfig>(da_config).is_ok_and(|celestia_config| celestia_config.local_devnet)
+}
+
+/// Starts the local `celestia-devnet` container, shared by all the app chains, if not running
+/// already
+async fn start_devnet() -> Result<(), CelestiaError> {
+    if is_container_running(CELESTIA_DEVNET_CONTAINER_NAME).await? {
+        return Ok(());
+    }
+    if container_exists(CELESTIA_DEVNET_CONTAINER_NAME).await? {
+        kill_container(CELESTIA_DEVNET_CONTAINER_NAME).await?;
+    }
+
+    let mut port_bindings = HashMap::new();
+    for port in CELESTIA_DEVNET_PORTS {
+        port_bindings.insert(
+            format!("{}/tcp", port),
+            Some(vec![PortBinding { host_ip: Some("0.0.0.0".to_string()), host_port: Some(port.to_string()) }]),
+        );
+    }
+    let host_config = HostConfig { port_bindings: Some(port_bindings), ..Default::default() };
+
+    run_docker_image(CELESTIA_DEVNET_IMAGE, CELESTIA_DEVNET_CONTAINER_NAME, None, None, Some(host_config)).await?;
+    log::info!("🌌 celestia-devnet is running on {}", CELESTIA_DEVNET_PROVIDER);
+
+    Ok(())
+}
+
+/// The bridge node of the devnet issues the auth token once it has started
+async fn get_devnet_auth_token() -> Result<String, CelestiaError> {
+    for _ in 0..DEVNET_STARTUP_ATTEMPTS {
+        let output = exec_in_container(
+            CELESTIA_DEVNET_CONTAINER_NAME,
+            vec!["celestia", "bridge", "auth", "admin", "--node.store", "/home/celestia/bridge"],
+        )
+        .await;
+        if let Ok(output) = output {
+            // the token is a JWT printed after the node logs
+            if let Some(token) = output.lines().map(str::trim).rfind(|line| line.starts_with("ey")) {
+                return Ok(token.to_string());
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    Err(CelestiaError::FailedToGetAuthToken)
+}
+
+async fn get_balance(celestia_config: &CelestiaConfig) -> Result<u64, CelestiaError> {
+    let mut request = Client::new()
+        .post(&celestia_config.http_provider)
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "state.Balance", "params": [] }));
+    if let Some(auth_token) = &celestia_config.auth_token {
+        request = request.bearer_auth(auth_token);
+    }
+
+    parse_balance(request.send().await?.json::<RpcResponse<Balance>>().await?)
+}
+
+fn parse_balance(response: RpcResponse<Balance>) -> Result<u64, CelestiaError> {
+    match (response.result, response.error) {
+        (Some(balance), _) => balance.amount.parse().map_err(|_| CelestiaError::NodeError(balance.amount)),
+        (None, Some(error)) => Err(CelestiaError::NodeError(error.to_string())),
+        (None, None) => Err(CelestiaError::NodeError("empty response".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_response(response: &str) -> Result<u64, CelestiaError> {
+        parse_balance(serde_json::from_str(response).unwrap())
+    }
+
+    #[test]
+    fn parse_da_config_fills_defaults() {
+        let da_config = json!({
+            "http_provider": "http://localhost:26658",
+            "ws_provider": "ws://localhost:26658",
+            "nid": "namespace",
+            "mode": "sovereign",
+        });
+        let da_config = CelestiaClient.parse_da_config(da_config).unwrap();
+
+        assert_eq!(da_config["auth_token"], serde_json::Value::Null);
+        assert_eq!(da_config["min_balance"], default_min_balance());
+        assert_eq!(da_config["funding_timeout_secs"], default_funding_timeout_secs());
+        assert_eq!(da_config["local_devnet"], false);
+    }
+
+    #[test]
+    fn parse_da_config_rejects_missing_namespace() {
+        let da_config = json!({ "http_provider": "http://localhost:26658", "ws_provider": "", "mode": "sovereign" });
+        assert!(CelestiaClient.parse_da_config(da_config).is_err());
+    }
+
+    #[test]
+    fn devnet_users() {
+        let devnet = json!({
+            "http_provider": CELESTIA_DEVNET_PROVIDER,
+            "ws_provider": "ws://localhost:26658",
+            "nid": "namespace",
+            "mode": "sovereign",
+            "local_devnet": true,
+        });
+        let light_node = json!({
+            "http_provider": "http://light-node:26658",
+            "ws_provider": "ws://light-node:26658",
+            "nid": "namespace",
+            "mode": "sovereign",
+        });
+        let ethereum = json!({ "http_provider": "http://localhost:8545", "local_anvil": true });
+
+        assert!(uses_devnet(&devnet.to_string()));
+        assert!(!uses_devnet(&light_node.to_string()));
+        assert!(!uses_devnet(&ethereum.to_string()));
+        assert!(!uses_devnet("not json"));
+    }
+
+    #[test]
+    fn parse_balance_response() {
+        let balance = parse_response(r#"{"result": {"amount": "1000000", "denom": "utia"}}"#).unwrap();
+        assert_eq!(balance, 1_000_000);
+
+        let error = parse_response(r#"{"error": {"code": 1, "message": "unauthorized"}}"#).unwrap_err();
+        assert!(matches!(error, CelestiaError::NodeError(message) if message.contains("unauthorized")));
+
+        let error = parse_response(r#"{"result": {"amount": "-1"}}"#).unwrap_err();
+        assert!(matches!(error, CelestiaError::NodeError(amount) if amount == "-1"));
+
+        assert!(matches!(parse_response("{}"), Err(CelestiaError::NodeError(_))));
+    }
+}
@@ -2,16 +2,19 @@ use async_trait::async_trait;
 use std::io;
 use std::path::PathBuf;
 
+use clap::Args;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumIter, EnumString};
 use thiserror::Error;
 
-use crate::app::config::AppChainConfig;
+use crate::app::config::{AppChainConfig, RollupMode};
 use crate::da::avail::{AvailClient, AvailError};
+use crate::da::celestia::{CelestiaClient, CelestiaError};
 use crate::da::ethereum::EthereumClient;
 use crate::da::ethereum::EthereumError;
 use crate::da::no_da::NoDAConfig;
 use crate::utils::constants::APP_DA_CONFIG_NAME;
+use crate::utils::errors::KeystoreError;
 use crate::utils::paths::get_app_home;
 use eyre::Result as EyreResult;
 
@@ -19,6 +22,7 @@ use eyre::Result as EyreResult;
 pub enum DALayer {
     Avail,
     Ethereum,
+    Celestia,
     NoDA,
 }
 
@@ -28,6 +32,8 @@ pub enum DaError {
     AvailError(#[from] AvailError),
     #[error("ethereum error: {0}")]
     EthereumError(#[from] EthereumError),
+    #[error("celestia error: {0}")]
+    CelestiaError(#[from] CelestiaError),
     #[error("failed to read app home: {0}")]
     FailedToReadAppHome(io::Error),
     #[error("inquire error")]
@@ -40,19 +46,97 @@ pub enum DaError {
     FailedToSerializeDaConfig(serde_json::Error),
     #[error("Failed to write DA config to file")]
     FailedToWriteDaConfigToFile(io::Error),
+    #[error("{0} mode is not supported by the {1} DA layer")]
+    UnsupportedMode(RollupMode, DALayer),
+    #[error("keystore error: {0}")]
+    KeystoreError(#[from] KeystoreError),
+    #[error("{0} is not supported by the {1} DA layer")]
+    UnsupportedAccountOption(String, DALayer),
+}
+
+/// Existing account of the DA layer to import at `init`, instead of generating a new one
+#[derive(Debug, Default, Args)]
+pub struct DaAccountOpts {
+    /// Seed or secret phrase of the Avail account
+    #[clap(long = "da-seed", conflicts_with = "mnemonic_file")]
+    pub seed: Option<String>,
+    /// File holding the secret phrase of the Avail account
+    #[clap(long = "da-mnemonic-file")]
+    pub mnemonic_file: Option<PathBuf>,
+    /// Private key of the Ethereum sequencer
+    #[clap(long = "da-private-key", conflicts_with = "keystore")]
+    pub private_key: Option<String>,
+    /// Web3 Secret Storage file of the Ethereum sequencer
+    #[clap(long = "da-keystore")]
+    pub keystore: Option<PathBuf>,
+}
+
+impl DaAccountOpts {
+    /// Fails if an option not supported by the DA layer is set
+    pub fn check_supported(&self, da_layer: &DALayer, supported: &[&str]) -> Result<(), DaError> {
+        let given = [
+            ("--da-seed", self.seed.is_some()),
+            ("--da-mnemonic-file", self.mnemonic_file.is_some()),
+            ("--da-private-key", self.private_key.is_some()),
+            ("--da-keystore", self.keystore.is_some()),
+        ];
+        match given.into_iter().find(|(option, given)| *given && !supported.contains(option)) {
+            Some((option, _)) => Err(DaError::UnsupportedAccountOption(option.to_string(), da_layer.clone())),
+            None => Ok(()),
+        }
+    }
 }
 
 #[async_trait]
-pub trait DaClient {
-    fn setup_and_generate_keypair(&self, config: &AppChainConfig) -> Result<(), DaError>;
+pub trait DaClient: Send + Sync {
+    fn supported_modes(&self) -> Vec<RollupMode>;
+
+    /// Fails if the DA layer can't be used with the rollup mode of the app chain
+    fn validate_mode(&self, config: &AppChainConfig) -> Result<(), DaError> {
+        match self.supported_modes().contains(&config.mode) {
+            true => Ok(()),
+            false => Err(DaError::UnsupportedMode(config.mode.clone(), config.da_layer.clone())),
+        }
+    }
+
+    /// Options of `init` importing an existing account of the DA layer
+    fn supported_account_opts(&self) -> Vec<&'static str> {
+        vec![]
+    }
+
+    async fn setup_and_generate_keypair(&self, config: &AppChainConfig, account: &DaAccountOpts)
+        -> Result<(), DaError>;
 
-    fn confirm_minimum_balance(&self, config: &AppChainConfig) -> Result<(), DaError>;
+    async fn confirm_minimum_balance(&self, config: &AppChainConfig) -> Result<(), DaError>;
+
+    /// Checks the DA config against the config struct of the DA layer, returns it with the
+    /// defaults filled in
+    fn parse_da_config(&self, da_config: serde_json::Value) -> Result<serde_json::Value, DaError> {
+        Ok(da_config)
+    }
 
     fn get_da_config_path(&self, config: &AppChainConfig) -> Result<PathBuf, DaError> {
         Ok(get_app_home(&config.app_chain).map_err(DaError::FailedToReadAppHome)?.join(APP_DA_CONFIG_NAME))
     }
 
+    /// Path of the DA config given to Madara. DA layers keeping their secrets in the keystore
+    /// unlock it and return a copy of the config with the secrets injected, which is removed
+    /// when the node stops.
+    async fn unlock_da_config(&self, config: &AppChainConfig) -> Result<PathBuf, DaError> {
+        self.get_da_config_path(config)
+    }
+
     async fn setup(&self, config: &AppChainConfig) -> EyreResult<()>;
+
+    /// Removes the DA services managed by the CLI when the app chain is deleted
+    async fn teardown(&self, _config: &AppChainConfig) -> Result<(), DaError> {
+        Ok(())
+    }
+
+    /// Forgets the DA state tied to the chain state, called when the chain is reset
+    async fn reset(&self, _config: &AppChainConfig) -> Result<(), DaError> {
+        Ok(())
+    }
 }
 
 pub struct DAFactory;
@@ -62,6 +146,7 @@ impl DAFactory {
         match da {
             DALayer::Avail => Box::new(AvailClient {}),
             DALayer::Ethereum => Box::new(EthereumClient {}),
+            DALayer::Celestia => Box::new(CelestiaClient {}),
             _ => Box::new(NoDAConfig {}),
         }
     }
@@ -1,19 +1,35 @@
-use crate::app::config::AppChainConfig;
-use crate::da::da_layers::{DaClient, DaError};
+use crate::app::config::{AppChainConfig, RollupMode};
+use crate::da::da_layers::{DaAccountOpts, DaClient, DaError};
 use crate::utils::serde::bytes_from_hex_str;
 use async_trait::async_trait;
 use eyre::Result as EyreResult;
 
 use ethers::contract::abigen;
 
-use ethers::middleware::SignerMiddleware;
-use ethers::providers::{Http, Provider};
+use ethers::middleware::{Middleware, SignerMiddleware};
+use ethers::providers::{Http, Provider, ProviderError};
 use ethers::signers::{LocalWallet, Signer, WalletError};
+use ethers::abi::{encode, Token};
+use ethers::types::{Address, Bytes, I256, U256};
+use ethers::utils::{parse_ether, ConversionError};
 
+use bollard::models::{HostConfig, PortBinding};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::path::PathBuf;
 
-use crate::cli::prompt::get_boolean_input;
+use crate::cli::prompt::{
+    get_custom_input, get_option, get_password_input, get_text_input, ANVIL_PORT, ETHEREUM_ACCOUNT, ETHEREUM_CHAIN_ID,
+    ETHEREUM_KEYSTORE, ETHEREUM_KEYSTORE_PASSWORD, ETHEREUM_NODE, ETHEREUM_PRIVATE_KEY, ETHEREUM_PROVIDER,
+    ETHEREUM_VERIFIER,
+};
+use crate::utils::docker::{container_exists, is_container_running, kill_container, run_docker_image};
+use crate::utils::errors::DockerError;
+use crate::utils::keystore::{
+    get_keystore_password, get_keystore_path, import_ethereum_keystore, read_ethereum_keystore,
+    write_ethereum_keystore, write_unlocked_da_config,
+};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -24,11 +40,92 @@ pub struct EthereumClient;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EthereumConfig {
     pub http_provider: String,
+    /// Address of the Starknet core contract proxy, written back once deployed
     pub core_contracts: String,
+    /// Left empty when the key is in the keystore, a key set here takes precedence
     pub sequencer_key: String,
     pub chain_id: u32,
     pub mode: String,
     pub poll_interval_ms: u32,
+    /// Whether the node is an Anvil container managed by the CLI and removed with the app chain
+    #[serde(default)]
+    pub local_anvil: bool,
+    #[serde(default = "default_anvil_port")]
+    pub anvil_port: u16,
+    #[serde(default)]
+    pub deployment: Option<CoreContractsDeployment>,
+    /// Parameters the Starknet core contract is initialized with
+    #[serde(default)]
+    pub init_params: StarknetInitParams,
+}
+
+/// Initial state of the Starknet core contract, the defaults match the pinned Madara version
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StarknetInitParams {
+    /// Hash of the Starknet OS program
+    pub program_hash: String,
+    /// Address of the fact registry verifying the proofs
+    pub verifier: String,
+    /// Hash of the Starknet OS config
+    pub config_hash: String,
+    /// Global state root of the genesis state
+    pub global_root: String,
+    /// Number of the latest block of the genesis state
+    pub block_number: i64,
+    pub block_hash: String,
+}
+
+impl Default for StarknetInitParams {
+    fn default() -> Self {
+        StarknetInitParams {
+            program_hash: "0x41fc2a467ef8649580631912517edcab7674173f1dbfa2e9b64fbcd82bc4d79".to_string(),
+            verifier: format!("{:?}", Address::zero()),
+            config_hash: "0x036f5e4ea4dd042801c8841e3db8e654124305da0f11824fc1db60c405dbb39f".to_string(),
+            global_root: "0x0".to_string(),
+            block_number: 0,
+            block_hash: "0x0".to_string(),
+        }
+    }
+}
+
+impl StarknetInitParams {
+    pub fn is_verifier_unset(&self) -> bool {
+        Address::from_str(&self.verifier).map(|verifier| verifier.is_zero()).unwrap_or(true)
+    }
+
+    /// ABI encodes the parameters as expected by `initialize`: the external initializer of the
+    /// proxy followed by the program hash, verifier, config hash and the genesis state
+    pub fn encode(&self) -> Result<Bytes, EthereumError> {
+        let tokens = vec![
+            // no external initializer
+            Token::Address(Address::zero()),
+            Token::Uint(parse_hash("program_hash", &self.program_hash)?),
+            Token::Address(
+                Address::from_str(&self.verifier)
+                    .map_err(|_| EthereumError::InvalidInitParam("verifier".to_string(), self.verifier.clone()))?,
+            ),
+            Token::Uint(parse_hash("config_hash", &self.config_hash)?),
+            Token::Uint(parse_hash("global_root", &self.global_root)?),
+            Token::Int(I256::from(self.block_number).into_raw()),
+            Token::Uint(parse_hash("block_hash", &self.block_hash)?),
+        ];
+        Ok(encode(&tokens).into())
+    }
+}
+
+fn parse_hash(name: &str, hash: &str) -> Result<U256, EthereumError> {
+    let bytes = bytes_from_hex_str::<32, true>(hash)
+        .map_err(|err| EthereumError::InvalidInitParam(name.to_string(), err.to_string()))?;
+    Ok(U256::from_big_endian(&bytes))
+}
+
+/// Where and when the Starknet core contracts were deployed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CoreContractsDeployment {
+    pub block_number: u64,
+    pub starknet_address: String,
+    pub starknet_tx_hash: String,
+    pub proxy_tx_hash: String,
 }
 
 #[derive(Error, Debug)]
@@ -39,70 +136,186 @@ pub enum EthereumError {
     FailedToSetupStarknet,
     #[error("Anvil node not running")]
     AnvilNodeNotRunning,
+    #[error("Failed to fund the sequencer account: {0}")]
+    FailedToFundAccount(#[from] ProviderError),
+    #[error("Ethereum node not reachable at {0}")]
+    NodeNotReachable(String),
+    #[error("Ethereum node has chain id {actual}, but {expected} is configured")]
+    ChainIdMismatch { expected: u32, actual: u64 },
+    #[error("Failed to manage the Anvil container: {0}")]
+    FailedToManageAnvil(#[from] DockerError),
+    #[error("No receipt for the deployment of {0}")]
+    MissingDeploymentReceipt(String),
+    #[error("Invalid Starknet init param `{0}`: {1}")]
+    InvalidInitParam(String, String),
+    #[error("A verifier address is needed in validity mode")]
+    MissingVerifier,
+    #[error("Invalid ETH amount `{0}`: {1}")]
+    InvalidEthAmount(String, ConversionError),
 }
 
 const ANVIL_DOCS: &str = "https://github.com/foundry-rs/foundry/tree/master/crates/anvil";
+/// Pinned so that the devnet behaves the same on every machine
+const ANVIL_IMAGE: &str = "ghcr.io/foundry-rs/foundry:v1.0.0";
+const ANVIL_DEFAULT_CHAIN_ID: u32 = 31337;
+const NODE_STARTUP_ATTEMPTS: u32 = 30;
+/// Balance the sequencer account is topped up to on Anvil
+const ANVIL_SEQUENCER_BALANCE: &str = "100";
+
+const GENERATE_KEY_OPTION: &str = "Generate a new key";
+const IMPORT_KEY_OPTION: &str = "Import a private key";
+const IMPORT_KEYSTORE_OPTION: &str = "Import a keystore file";
+
+const LOCAL_ANVIL_OPTION: &str = "Start a local Anvil devnet";
+const CUSTOM_NODE_OPTION: &str = "Use my own Ethereum node";
+
+fn default_anvil_port() -> u16 {
+    8545
+}
 
 #[async_trait]
 impl DaClient for EthereumClient {
-    fn setup_and_generate_keypair(&self, config: &AppChainConfig) -> Result<(), DaError> {
-        let file_path = self.get_da_config_path(config)?;
-        let file_path_str = file_path.to_string_lossy().to_string();
+    fn supported_modes(&self) -> Vec<RollupMode> {
+        vec![RollupMode::Sovereign, RollupMode::Validity]
+    }
+
+    fn parse_da_config(&self, da_config: serde_json::Value) -> Result<serde_json::Value, DaError> {
+        let ethereum_config: EthereumConfig =
+            serde_json::from_value(da_config).map_err(DaError::FailedToDeserializeDaConfig)?;
+        serde_json::to_value(ethereum_config).map_err(DaError::FailedToSerializeDaConfig)
+    }
+
+    fn supported_account_opts(&self) -> Vec<&'static str> {
+        vec!["--da-private-key", "--da-keystore"]
+    }
+
+    async fn setup_and_generate_keypair(
+        &self,
+        config: &AppChainConfig,
+        account: &DaAccountOpts,
+    ) -> Result<(), DaError> {
+        let local_anvil = get_option(
+            ETHEREUM_NODE,
+            "Which Ethereum node should the app chain settle on?",
+            vec![LOCAL_ANVIL_OPTION, CUSTOM_NODE_OPTION],
+        )? == LOCAL_ANVIL_OPTION;
+
+        let (http_provider, anvil_port) = if local_anvil {
+            let anvil_port = get_custom_input::<u16>(
+                ANVIL_PORT,
+                "Enter the port of the Anvil devnet:",
+                Some(default_anvil_port()),
+                Some("Use a different port for every app chain running at the same time"),
+            )?;
+            (format!("http://localhost:{}", anvil_port), anvil_port)
+        } else {
+            let http_provider = get_text_input(
+                ETHEREUM_PROVIDER,
+                "Enter the RPC endpoint of your Ethereum node:",
+                Some("http://localhost:8545"),
+            )?;
+            (http_provider, default_anvil_port())
+        };
+        let chain_id = get_custom_input::<u32>(
+            ETHEREUM_CHAIN_ID,
+            "Enter the chain id of the Ethereum network:",
+            Some(ANVIL_DEFAULT_CHAIN_ID),
+            None,
+        )?;
+
+        let mut init_params = StarknetInitParams::default();
+        if config.mode == RollupMode::Validity {
+            init_params.verifier = format!(
+                "{:?}",
+                get_custom_input::<Address>(
+                    ETHEREUM_VERIFIER,
+                    "Enter the address of the verifier (fact registry) contract:",
+                    None,
+                    Some("Proofs of the app chain are checked against this contract"),
+                )?
+            );
+        }
+
+        let wallet = setup_sequencer_keystore(&config.app_chain, account)?;
+        log::info!("🔑 Sequencer key encrypted in the keystore: {}", get_keystore_path(&config.app_chain)?.display());
+        log::info!("💧 Ethereum sequencer address: {:?}", wallet.address());
+        log::info!("📕 The address is funded automatically on Anvil, please fund it on any other network.");
 
-        // TODO: generate a new random key for every new app chain
         let ethereum_config = EthereumConfig {
-            http_provider: "http://localhost:8545".to_string(),
-            core_contracts: "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512".to_string(),
-            // default anvil key
-            sequencer_key: "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".to_string(),
-            chain_id: 31337,
-            mode: "sovereign".to_string(),
+            http_provider,
+            // deployed on the first run
+            core_contracts: format!("{:?}", Address::zero()),
+            sequencer_key: String::new(),
+            chain_id,
+            mode: config.mode.da_mode(),
             poll_interval_ms: 10,
+            local_anvil,
+            anvil_port,
+            deployment: None,
+            init_params,
         };
 
-        fs::write(file_path_str, serde_json::to_string(&ethereum_config).map_err(DaError::FailedToSerializeDaConfig)?)
-            .map_err(DaError::FailedToWriteDaConfigToFile)?;
+        self.write_config(config, &ethereum_config)?;
 
         Ok(())
     }
 
-    fn confirm_minimum_balance(&self, _config: &AppChainConfig) -> Result<(), DaError> {
+    async fn confirm_minimum_balance(&self, _config: &AppChainConfig) -> Result<(), DaError> {
         Ok(())
     }
 
-    async fn setup(&self, config: &AppChainConfig) -> EyreResult<()> {
-        match get_boolean_input(
-            format!("Are you running an Anvil node locally? The CLI tool has been tested on Anvil version 0.2.0 (c312c0d). Docs: {}", ANVIL_DOCS).as_str(),
-            Some(true),
-        )? {
-            true => Ok(()),
-            false => Err(DaError::EthereumError(EthereumError::AnvilNodeNotRunning)),
-        }?;
+    async fn unlock_da_config(&self, config: &AppChainConfig) -> Result<PathBuf, DaError> {
+        let mut ethereum_config = self.read_config(config)?;
+        if !ethereum_config.sequencer_key.is_empty() {
+            return self.get_da_config_path(config);
+        }
 
-        let ethereum_config_path = self.get_da_config_path(config)?;
-        let ethereum_config: EthereumConfig = serde_json::from_str(
-            fs::read_to_string(ethereum_config_path).map_err(DaError::FailedToReadDaConfigFile)?.as_str(),
-        )
-        .map_err(DaError::FailedToDeserializeDaConfig)?;
+        let wallet = self.get_sequencer_wallet(config, &ethereum_config)?;
+        ethereum_config.sequencer_key = hex::encode(wallet.signer().to_bytes());
+        let ethereum_config = serde_json::to_value(ethereum_config).map_err(DaError::FailedToSerializeDaConfig)?;
+
+        Ok(write_unlocked_da_config(&config.app_chain, &ethereum_config)?)
+    }
+
+    async fn setup(&self, config: &AppChainConfig) -> EyreResult<()> {
+        let mut ethereum_config = self.read_config(config)?;
+        if config.mode == RollupMode::Validity && ethereum_config.init_params.is_verifier_unset() {
+            return Err(EthereumError::MissingVerifier.into());
+        }
+        if ethereum_config.local_anvil {
+            start_anvil(&config.app_chain, &ethereum_config).await?;
+        }
 
         // get wallet
-        let wallet =
-            LocalWallet::from_str(&ethereum_config.sequencer_key).map_err(EthereumError::FailedToCreateWallet)?;
+        let wallet = self.get_sequencer_wallet(config, &ethereum_config)?;
 
         // connect to the network
         let provider = Provider::<Http>::try_from(ethereum_config.http_provider.as_str())
             .map_err(|_| EthereumError::FailedToSetupStarknet)?
             .interval(Duration::from_millis(10u64));
+        wait_for_node(&provider, &ethereum_config).await?;
+
+        if is_anvil(&provider).await? {
+            fund_on_anvil(&provider, wallet.address()).await?;
+        }
+
+        // skip the deployment if the contracts survived since the last run
+        let core_contracts = Address::from_str(&ethereum_config.core_contracts)?;
+        if !core_contracts.is_zero() && !provider.get_code(core_contracts, None).await?.is_empty() {
+            log::info!("📜 Starknet core contracts already deployed at {:?}", core_contracts);
+            return Ok(());
+        }
 
         // instantiate the client with the wallet
         let client = Arc::new(SignerMiddleware::new(provider, wallet.clone().with_chain_id(ethereum_config.chain_id)));
 
         // deploye Starknet core contract
         abigen!(Starknet, "src/assets/Starknet.json");
-        let starknet_contract = Starknet::deploy(client.clone(), ())?.send().await?;
+        let (starknet_contract, starknet_receipt) = Starknet::deploy(client.clone(), ())?.send_with_receipt().await?;
 
         abigen!(UnsafeProxy, "src/assets/UnsafeProxy.json");
-        let proxy_contract = UnsafeProxy::deploy(client.clone(), starknet_contract.address())?.send().await?;
+        let (proxy_contract, proxy_receipt) =
+            UnsafeProxy::deploy(client.clone(), starknet_contract.address())?.send_with_receipt().await?;
 
         abigen!(
             StarknetInitializer,
@@ -113,22 +326,253 @@ impl DaClient for EthereumClient {
         );
         let initializer = StarknetInitializer::new(proxy_contract.address(), client);
 
-        let mut bytes = [0u8; 7 * 32];
-        bytes[32..64].copy_from_slice(
-            bytes_from_hex_str::<32, true>("0x41fc2a467ef8649580631912517edcab7674173f1dbfa2e9b64fbcd82bc4d79")?
-                .as_slice(),
-        );
-        bytes[96..128].copy_from_slice(
-            bytes_from_hex_str::<32, true>("0x036f5e4ea4dd042801c8841e3db8e654124305da0f11824fc1db60c405dbb39f")?
-                .as_slice(),
-        );
-
         // 1. Provide Starknet OS program/config and genesis state
-        initializer.initialize(bytes.into()).send().await?.await?;
+        initializer.initialize(ethereum_config.init_params.encode()?).send().await?.await?;
 
         // 2. Add our EOA as Starknet operator
         initializer.register_operator(wallet.address()).send().await?.await?;
 
+        let block_number = proxy_receipt
+            .block_number
+            .ok_or(EthereumError::MissingDeploymentReceipt("UnsafeProxy".to_string()))?
+            .as_u64();
+        ethereum_config.core_contracts = format!("{:?}", proxy_contract.address());
+        ethereum_config.deployment = Some(CoreContractsDeployment {
+            block_number,
+            starknet_address: format!("{:?}", starknet_contract.address()),
+            starknet_tx_hash: format!("{:?}", starknet_receipt.transaction_hash),
+            proxy_tx_hash: format!("{:?}", proxy_receipt.transaction_hash),
+        });
+        self.write_config(config, &ethereum_config)?;
+        log::info!(
+            "📜 Starknet core contracts deployed at {} (block {})",
+            ethereum_config.core_contracts,
+            block_number
+        );
+
         Ok(())
     }
+
+    /// The core contracts hold the state of the chain, so they are deployed again on the next run,
+    /// on a new Anvil devnet if the CLI manages it
+    async fn reset(&self, config: &AppChainConfig) -> Result<(), DaError> {
+        self.teardown(config).await?;
+        let mut ethereum_config = self.read_config(config)?;
+        ethereum_config.core_contracts = format!("{:?}", Address::zero());
+        ethereum_config.deployment = None;
+        self.write_config(config, &ethereum_config)?;
+        log::info!("📜 Starknet core contracts will be deployed again on the next run");
+        Ok(())
+    }
+
+    /// Anvil keeps its state in memory, so it keeps running while the app chain is stopped and is
+    /// only removed along with the chain state
+    async fn teardown(&self, config: &AppChainConfig) -> Result<(), DaError> {
+        let ethereum_config = self.read_config(config)?;
+        let container_name = get_anvil_container_name(&config.app_chain);
+        if ethereum_config.local_anvil && container_exists(&container_name).await.map_err(EthereumError::from)? {
+            kill_container(&container_name).await.map_err(EthereumError::from)?;
+            log::info!("⛓️ Anvil devnet of {} removed", config.app_chain);
+        }
+        Ok(())
+    }
+}
+
+impl EthereumClient {
+    /// Wallet of the sequencer, from the key set in the config if any or else from the keystore
+    fn get_sequencer_wallet(
+        &self,
+        config: &AppChainConfig,
+        ethereum_config: &EthereumConfig,
+    ) -> Result<LocalWallet, DaError> {
+        if !ethereum_config.sequencer_key.is_empty() {
+            return Ok(
+                LocalWallet::from_str(&ethereum_config.sequencer_key).map_err(EthereumError::FailedToCreateWallet)?
+            );
+        }
+        let password = get_keystore_password(false)?;
+        Ok(read_ethereum_keystore(&config.app_chain, &password)?)
+    }
+
+    fn read_config(&self, config: &AppChainConfig) -> Result<EthereumConfig, DaError> {
+        let ethereum_config_path = self.get_da_config_path(config)?;
+        serde_json::from_str(
+            fs::read_to_string(ethereum_config_path).map_err(DaError::FailedToReadDaConfigFile)?.as_str(),
+        )
+        .map_err(DaError::FailedToDeserializeDaConfig)
+    }
+
+    fn write_config(&self, config: &AppChainConfig, ethereum_config: &EthereumConfig) -> Result<(), DaError> {
+        let ethereum_config_path = self.get_da_config_path(config)?;
+        fs::write(
+            ethereum_config_path,
+            serde_json::to_string(ethereum_config).map_err(DaError::FailedToSerializeDaConfig)?,
+        )
+        .map_err(DaError::FailedToWriteDaConfigToFile)
+    }
+}
+
+fn get_anvil_container_name(app_chain: &str) -> String {
+    format!("anvil-{}", app_chain)
+}
+
+/// Starts the Anvil devnet of the app chain if not running already
+async fn start_anvil(app_chain: &str, ethereum_config: &EthereumConfig) -> Result<(), EthereumError> {
+    let container_name = get_anvil_container_name(app_chain);
+    if is_container_running(&container_name).await? {
+        return Ok(());
+    }
+    if container_exists(&container_name).await? {
+        kill_container(&container_name).await?;
+    }
+
+    let port = ethereum_config.anvil_port;
+    let mut port_bindings = HashMap::new();
+    port_bindings.insert(
+        format!("{}/tcp", port),
+        Some(vec![PortBinding { host_ip: Some("0.0.0.0".to_string()), host_port: Some(port.to_string()) }]),
+    );
+    let host_config = HostConfig { port_bindings: Some(port_bindings), ..Default::default() };
+
+    // the entrypoint of the foundry image is a shell
+    let cmd = format!("anvil --host 0.0.0.0 --port {} --chain-id {}", port, ethereum_config.chain_id);
+    run_docker_image(ANVIL_IMAGE, &container_name, None, Some(vec![cmd.as_str()]), Some(host_config)).await?;
+    log::info!("⛓️ Anvil is running on {}. Docs: {}", ethereum_config.http_provider, ANVIL_DOCS);
+
+    Ok(())
+}
+
+/// Waits for the node to answer over JSON-RPC and checks it is on the configured chain
+async fn wait_for_node(provider: &Provider<Http>, ethereum_config: &EthereumConfig) -> Result<(), EthereumError> {
+    for _ in 0..NODE_STARTUP_ATTEMPTS {
+        if let Ok(chain_id) = provider.get_chainid().await {
+            if chain_id != U256::from(ethereum_config.chain_id) {
+                return Err(EthereumError::ChainIdMismatch {
+                    expected: ethereum_config.chain_id,
+                    actual: chain_id.low_u64(),
+                });
+            }
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    match ethereum_config.local_anvil {
+        true => Err(EthereumError::AnvilNodeNotRunning),
+        false => Err(EthereumError::NodeNotReachable(ethereum_config.http_provider.clone())),
+    }
+}
+
+/// Generates a new sequencer key, so that app chains sharing a node don't clash on nonces,
+/// or imports the one given with `--da-private-key` or `--da-keystore`, or prompted for.
+/// The key is kept in the keystore of the app chain.
+fn setup_sequencer_keystore(app_chain: &str, account: &DaAccountOpts) -> Result<LocalWallet, DaError> {
+    let option = match (&account.private_key, &account.keystore) {
+        (Some(_), _) => IMPORT_KEY_OPTION,
+        (None, Some(_)) => IMPORT_KEYSTORE_OPTION,
+        (None, None) => get_option(
+            ETHEREUM_ACCOUNT,
+            "Which account should the sequencer use?",
+            vec![GENERATE_KEY_OPTION, IMPORT_KEY_OPTION, IMPORT_KEYSTORE_OPTION],
+        )?,
+    };
+
+    let wallet = match option {
+        IMPORT_KEY_OPTION => {
+            let private_key = match &account.private_key {
+                Some(private_key) => private_key.clone(),
+                None => get_password_input(ETHEREUM_PRIVATE_KEY, "Enter the private key of the sequencer:")?,
+            };
+            LocalWallet::from_str(private_key.trim().trim_start_matches("0x"))
+                .map_err(EthereumError::FailedToCreateWallet)?
+        }
+        IMPORT_KEYSTORE_OPTION => {
+            let keystore = match &account.keystore {
+                Some(keystore) => keystore.clone(),
+                None => PathBuf::from(get_text_input(ETHEREUM_KEYSTORE, "Enter the path of the keystore file:", None)?),
+            };
+            let password = get_password_input(ETHEREUM_KEYSTORE_PASSWORD, "Enter the password of the keystore:")?;
+            let wallet =
+                LocalWallet::decrypt_keystore(&keystore, password).map_err(EthereumError::FailedToCreateWallet)?;
+            // the keystore is copied as is, so it keeps its password
+            import_ethereum_keystore(app_chain, &keystore)?;
+            return Ok(wallet);
+        }
+        _ => LocalWallet::new(&mut rand::thread_rng()),
+    };
+
+    let password = get_keystore_password(true)?;
+    write_ethereum_keystore(app_chain, &wallet.signer().to_bytes(), &password)?;
+
+    Ok(wallet)
+}
+
+async fn is_anvil(provider: &Provider<Http>) -> Result<bool, EthereumError> {
+    let client_version = provider.client_version().await?;
+    Ok(client_version.to_lowercase().starts_with("anvil"))
+}
+
+/// Tops up the sequencer account using the `anvil_setBalance` dev RPC
+async fn fund_on_anvil(provider: &Provider<Http>, address: Address) -> Result<(), EthereumError> {
+    let target: U256 = parse_ether(ANVIL_SEQUENCER_BALANCE)
+        .map_err(|err| EthereumError::InvalidEthAmount(ANVIL_SEQUENCER_BALANCE.to_string(), err))?;
+    if provider.get_balance(address, None).await? >= target {
+        return Ok(());
+    }
+
+    provider.request::<_, ()>("anvil_setBalance", (address, target)).await?;
+    log::info!("💰 Funded the sequencer address {:?} with {} ETH on Anvil", address, ANVIL_SEQUENCER_BALANCE);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Size of an ABI encoded static parameter
+    const WORD: usize = 32;
+
+    fn word(encoded: &[u8], index: usize) -> &[u8] {
+        &encoded[index * WORD..(index + 1) * WORD]
+    }
+
+    #[test]
+    fn encode_default_init_params() {
+        let init_params = StarknetInitParams::default();
+        let encoded = init_params.encode().unwrap();
+
+        // external initializer, program hash, verifier, config hash, global root, block number and hash
+        assert_eq!(encoded.len(), 7 * WORD);
+        assert_eq!(word(&encoded, 0), [0; WORD]);
+        assert_eq!(hex::encode(word(&encoded, 1)), "041fc2a467ef8649580631912517edcab7674173f1dbfa2e9b64fbcd82bc4d79");
+        assert_eq!(word(&encoded, 2), [0; WORD]);
+        assert_eq!(hex::encode(word(&encoded, 3)), "036f5e4ea4dd042801c8841e3db8e654124305da0f11824fc1db60c405dbb39f");
+        assert_eq!(word(&encoded, 5), [0; WORD]);
+    }
+
+    #[test]
+    fn encode_verifier_and_block_number() {
+        let init_params = StarknetInitParams {
+            verifier: "0x00000000000000000000000000000000000000aa".to_string(),
+            block_number: -1,
+            ..Default::default()
+        };
+        let encoded = init_params.encode().unwrap();
+
+        let mut verifier = [0; WORD];
+        verifier[WORD - 1] = 0xaa;
+        assert_eq!(word(&encoded, 2), verifier);
+        // int256 is encoded in two's complement
+        assert_eq!(word(&encoded, 5), [0xff; WORD]);
+    }
+
+    #[test]
+    fn encode_rejects_invalid_params() {
+        let init_params = StarknetInitParams { verifier: "not an address".to_string(), ..Default::default() };
+        assert!(matches!(init_params.encode(), Err(EthereumError::InvalidInitParam(name, _)) if name == "verifier"));
+
+        let init_params = StarknetInitParams { global_root: "0xzz".to_string(), ..Default::default() };
+        assert!(matches!(init_params.encode(), Err(EthereumError::InvalidInitParam(name, _)) if name == "global_root"));
+    }
 }
@@ -3,4 +3,6 @@ pub mod da_layers;
 pub mod avail;
 
 mod ethereum;
+
+pub mod celestia;
 pub mod no_da;
@@ -1,17 +1,25 @@
 pub struct NoDAConfig;
 
-use crate::app::config::AppChainConfig;
-use crate::da::da_layers::{DaClient, DaError};
+use crate::app::config::{AppChainConfig, RollupMode};
+use crate::da::da_layers::{DaAccountOpts, DaClient, DaError};
 use async_trait::async_trait;
 
 #[async_trait]
 impl DaClient for NoDAConfig {
-    fn setup_and_generate_keypair(&self, config: &AppChainConfig) -> Result<(), DaError> {
+    fn supported_modes(&self) -> Vec<RollupMode> {
+        vec![RollupMode::Sovereign]
+    }
+
+    async fn setup_and_generate_keypair(
+        &self,
+        config: &AppChainConfig,
+        _account: &DaAccountOpts,
+    ) -> Result<(), DaError> {
         log::info!("Launching {} without any DA mode", config.app_chain);
         Ok(())
     }
 
-    fn confirm_minimum_balance(&self, _config: &AppChainConfig) -> Result<(), DaError> {
+    async fn confirm_minimum_balance(&self, _config: &AppChainConfig) -> Result<(), DaError> {
         Ok(())
     }
 
@@ -5,3 +5,5 @@ pub mod app;
 pub mod utils;
 
 pub mod da;
+
+pub mod binary;
@@ -1,15 +1,62 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::{env, io, process};
+
+use clap::{Args, Parser, Subcommand};
 use log::LevelFilter;
-use madara_cli::app::config::RollupMode;
+use madara_cli::app::config::{LogsConfig, RollupMode, Runtime};
+use madara_cli::binary::provider::BinarySource;
 use madara_cli::cli;
+use madara_cli::cli::errors::CliError;
 use madara_cli::cli::explorer::ExplorerOpts;
-use madara_cli::da::da_layers::DALayer;
+use madara_cli::cli::list::OutputFormat;
+use madara_cli::cli::logs::LogsOpts;
+use madara_cli::da::da_layers::{DALayer, DaAccountOpts};
+
+/// Seconds `run` and `restart` wait for the node to produce blocks
+const DEFAULT_HEALTH_TIMEOUT: u64 = 120;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Never prompt, use the defaults or fail when a required value is missing. Confirmations
+    /// default to no, pass --force to delete or reset.
+    #[clap(long = "non-interactive", global = true)]
+    non_interactive: bool,
+    /// Answer prompts from a TOML file, implies --non-interactive
+    #[clap(long = "from-file", value_name = "ANSWERS_FILE", global = true)]
+    answers_file: Option<PathBuf>,
+    /// Directory of the app chains and downloads, defaults to $MADARA_HOME or ~/.madara
+    #[clap(long, global = true)]
+    home: Option<PathBuf>,
+    /// File holding the keystore password, $MADARA_KEYSTORE_PASSWORD takes precedence
+    #[clap(long = "password-file", global = true)]
+    password_file: Option<PathBuf>,
+    /// Never reach GitHub or pull images, only use local clones and cached binaries
+    #[clap(long, global = true)]
+    offline: bool,
+}
+
+#[derive(Args)]
+struct BinaryOpts {
+    /// Path to a local Madara binary to use
+    #[clap(long = "madara-bin", conflicts_with = "build_from_source")]
+    madara_bin: Option<PathBuf>,
+    /// Build Madara from source instead of downloading a release
+    #[clap(long = "build-from-source")]
+    build_from_source: bool,
+}
+
+impl BinaryOpts {
+    fn source(&self) -> BinarySource {
+        BinarySource::from_flags(&self.madara_bin, self.build_from_source)
+    }
+
+    /// Binary source if one was explicitly asked for
+    fn explicit_source(&self) -> Option<BinarySource> {
+        (self.madara_bin.is_some() || self.build_from_source).then(|| self.source())
+    }
 }
 
 #[derive(Subcommand)]
@@ -25,19 +72,213 @@ enum Commands {
         /// Choose a supported DA Layer
         #[clap(short, long = "da-layer", value_enum, ignore_case = true)]
         da: Option<DALayer>,
+        /// Directory of the chain data, defaults to the data directory of the app chain
+        #[clap(long)]
+        base_path: Option<PathBuf>,
+        #[command(flatten)]
+        account: DaAccountOpts,
+        /// Tag, branch or commit of Madara to pin, defaults to the latest commit
+        #[clap(long = "madara-version")]
+        madara_version: Option<String>,
+        /// GitHub repository of Madara, e.g. a fork
+        #[clap(long = "madara-repo")]
+        madara_repo: Option<String>,
     },
     /// Lists all the existing App Chain configs
-    List,
+    List {
+        /// Output format
+        #[clap(short, long, value_enum, ignore_case = true, default_value_t)]
+        output: OutputFormat,
+    },
     /// Runs the App Chain using Madara
     Run {
         /// App chain name
         #[clap(short, long = "chain-name")]
         name: Option<String>,
+        #[clap(flatten)]
+        binary: BinaryOpts,
+        /// Run Madara on the host or inside Docker, remembered for the next runs
+        #[clap(long, value_enum, ignore_case = true)]
+        runtime: Option<Runtime>,
+        /// Seconds to wait for the node to produce blocks, 0 to not wait
+        #[clap(long, default_value_t = DEFAULT_HEALTH_TIMEOUT)]
+        health_timeout: u64,
         /// Additional arguments for Madara
         madara_flags: Vec<String>,
     },
-    /// Runs the L2 explorer
-    Explorer(ExplorerOpts),
+    /// Stops a running App Chain
+    Stop {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        /// Seconds to wait for a graceful shutdown before killing the node
+        #[clap(short, long, default_value_t = 30)]
+        timeout: u64,
+    },
+    /// Shows the status of the App Chains
+    Status {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+    },
+    /// Restarts the App Chain
+    Restart {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        /// Seconds to wait for a graceful shutdown before killing the node
+        #[clap(short, long, default_value_t = 30)]
+        timeout: u64,
+        #[clap(flatten)]
+        binary: BinaryOpts,
+        /// Seconds to wait for the node to produce blocks, 0 to not wait
+        #[clap(long, default_value_t = DEFAULT_HEALTH_TIMEOUT)]
+        health_timeout: u64,
+        /// Additional arguments for Madara, defaults to the ones of the running node
+        madara_flags: Vec<String>,
+    },
+    /// Deletes an App Chain along with its config, keys and data
+    Delete {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        /// Copy the config and keys to `archive` in the Madara home before deleting them
+        #[clap(long)]
+        archive_keys: bool,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        force: bool,
+    },
+    /// Wipes the chain data of an App Chain, keeping its config and keys
+    Reset {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        #[clap(flatten)]
+        binary: BinaryOpts,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        force: bool,
+    },
+    /// Moves an App Chain to another Madara version
+    Upgrade {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        /// Tag, branch or commit of Madara to pin, defaults to the latest commit
+        #[clap(long = "madara-version")]
+        madara_version: Option<String>,
+        /// GitHub repository of Madara, defaults to the one of the App Chain
+        #[clap(long = "madara-repo")]
+        madara_repo: Option<String>,
+    },
+    /// Lists the tagged Madara versions
+    Versions {
+        /// GitHub repository of Madara, e.g. a fork
+        #[clap(long = "madara-repo")]
+        madara_repo: Option<String>,
+    },
+    /// Checks that an App Chain is up and produces blocks, fails otherwise
+    Health {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        /// Seconds to wait for a new block
+        #[clap(short, long, default_value_t = 30)]
+        timeout: u64,
+    },
+    /// Shows the logs of an App Chain node or of the explorer
+    Logs(LogsOpts),
+    /// Runs the L2 explorer of an App Chain
+    #[command(args_conflicts_with_subcommands = true)]
+    Explorer {
+        #[command(subcommand)]
+        command: Option<ExplorerCommands>,
+        #[clap(flatten)]
+        opts: ExplorerOpts,
+    },
+    /// Manages the App Chain configs
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Writes the output of a node to its log file, rotating it
+    #[command(hide = true)]
+    LogWriter {
+        #[clap(long)]
+        file: PathBuf,
+        #[clap(long)]
+        max_size_mb: u64,
+        #[clap(long)]
+        max_files: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Shows the config and DA config of an App Chain
+    Show {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+    },
+    /// Prints a setting, keys of the DA config start with `da.`
+    Get {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        /// Dotted key, e.g. `runtime` or `da.ws_provider`
+        key: String,
+    },
+    /// Changes a setting, keys of the DA config start with `da.`
+    Set {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        /// Dotted key, e.g. `runtime` or `da.ws_provider`
+        key: String,
+        value: String,
+    },
+    /// Opens the config in $VISUAL or $EDITOR
+    Edit {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        /// Edit the DA config instead
+        #[clap(long)]
+        da: bool,
+    },
+    /// Checks the config and DA config of an App Chain
+    Validate {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+    },
+    /// Migrates the configs to the latest version, keeping a backup of the previous ones
+    Migrate {
+        /// App chain name, all the app chains if not set
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+        /// Shows the migrated configs without writing them
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExplorerCommands {
+    /// Stops the explorer of an App Chain
+    Stop {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+    },
+    /// Shows the explorer of an App Chain, or of all of them
+    Status {
+        /// App chain name
+        #[clap(short, long = "chain-name")]
+        name: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -51,11 +292,67 @@ async fn main() {
 
     let cli = Cli::parse();
 
+    if let Err(err) = execute(&cli).await {
+        log::error!("{}", err.report());
+        process::exit(err.exit_code());
+    }
+}
+
+async fn execute(cli: &Cli) -> Result<(), CliError> {
+    if let Some(home) = &cli.home {
+        // joining an absolute path replaces the current directory
+        madara_cli::utils::paths::set_madara_home(env::current_dir()?.join(home))?;
+    }
+    if let Some(password_file) = &cli.password_file {
+        madara_cli::utils::keystore::set_password_file(password_file.clone())?;
+    }
+    if cli.offline {
+        madara_cli::utils::github::set_offline(true)?;
+    }
+    cli::prompt::configure_prompts(cli.non_interactive, &cli.answers_file)?;
+
     match &cli.command {
-        Some(Commands::Init { name, mode, da }) => cli::init::init(name, mode, da).await,
-        Some(Commands::List) => cli::list::list(),
-        Some(Commands::Run { name, madara_flags }) => cli::run::run(name, madara_flags).await,
-        Some(Commands::Explorer(opts)) => cli::explorer::explorer(opts).await,
+        Some(Commands::Init { name, mode, da, base_path, account, madara_version, madara_repo }) => {
+            cli::init::init(name, mode, da, base_path, account, madara_version, madara_repo).await?
+        }
+        Some(Commands::List { output }) => cli::list::list(output).await?,
+        Some(Commands::Run { name, binary, runtime, health_timeout, madara_flags }) => {
+            cli::run::run(name, madara_flags, &binary.source(), runtime, *health_timeout).await?
+        }
+        Some(Commands::Stop { name, timeout }) => cli::stop::stop(name, *timeout).await?,
+        Some(Commands::Status { name }) => cli::status::status(name).await?,
+        Some(Commands::Restart { name, timeout, binary, health_timeout, madara_flags }) => {
+            cli::restart::restart(name, madara_flags, &binary.explicit_source(), *timeout, *health_timeout).await?
+        }
+        Some(Commands::Delete { name, archive_keys, force }) => {
+            cli::delete::delete(name, *archive_keys, *force).await?
+        }
+        Some(Commands::Reset { name, binary, force }) => cli::reset::reset(name, &binary.source(), *force).await?,
+        Some(Commands::Upgrade { name, madara_version, madara_repo }) => {
+            cli::upgrade::upgrade(name, madara_version, madara_repo).await?
+        }
+        Some(Commands::Versions { madara_repo }) => cli::versions::versions(madara_repo).await?,
+        Some(Commands::Health { name, timeout }) => cli::health::health(name, *timeout).await?,
+        Some(Commands::Logs(opts)) => cli::logs::logs(opts).await?,
+        Some(Commands::Explorer { command, opts }) => match command {
+            None => cli::explorer::explorer(opts).await?,
+            Some(ExplorerCommands::Stop { name }) => cli::explorer::stop(name).await?,
+            Some(ExplorerCommands::Status { name }) => cli::explorer::status(name).await?,
+        },
+        Some(Commands::Config { command }) => match command {
+            ConfigCommands::Show { name } => cli::config::show(name)?,
+            ConfigCommands::Get { name, key } => cli::config::get(name, key)?,
+            ConfigCommands::Set { name, key, value } => cli::config::set(name, key, value).await?,
+            ConfigCommands::Edit { name, da } => cli::config::edit(name, *da).await?,
+            ConfigCommands::Validate { name } => cli::config::validate_app_chain(name)?,
+            ConfigCommands::Migrate { name, dry_run } => cli::config::migrate(name, *dry_run)?,
+        },
+        Some(Commands::LogWriter { file, max_size_mb, max_files }) => {
+            let logs = LogsConfig { max_size_mb: *max_size_mb, max_files: *max_files };
+            madara_cli::utils::logs::write_logs(io::stdin().lock(), file, &logs)?
+        }
         None => log::info!("Use --help to see the complete list of available commands"),
     }
+
+    Ok(())
 }
@@ -1,4 +1,4 @@
-use std::io::{Error, ErrorKind};
+use std::io::Error;
 use std::path::PathBuf;
 use std::process::{Command, Output, Stdio};
 
@@ -22,7 +22,7 @@ pub fn execute_cmd_stdio<T: Into<Stdio>>(
                 log::debug!("Successfully executed {}", program);
                 Ok(output)
             } else {
-                Err(Error::new(ErrorKind::Other, "Unable to execute command"))
+                Err(Error::other("Unable to execute command"))
             }
         }
         Err(err) => {
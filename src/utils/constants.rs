@@ -6,4 +6,21 @@ pub const MADARA_BRANCH_NAME: &str = "cli_branch";
 
 pub const APP_CONFIG_NAME: &str = "config.toml";
 pub const APP_DA_CONFIG_NAME: &str = "da-config.json";
+/// DA config with the secrets of the keystore, only there while the node runs
+pub const APP_UNLOCKED_DA_CONFIG_NAME: &str = "da-config.unlocked.json";
 pub const APP_SECRET_PHRASE: &str = "secret-phrase.txt";
+pub const APP_KEYSTORE_NAME: &str = "keystore.json";
+pub const APP_NODE_STATE_NAME: &str = "node-state.json";
+pub const APP_NODE_LOG_NAME: &str = "madara.log";
+pub const APP_LOGS_DIR: &str = "logs";
+
+pub const MADARA_DEFAULT_RPC_PORT: u16 = 9944;
+pub const MADARA_DEFAULT_P2P_PORT: u16 = 30333;
+pub const MADARA_DEFAULT_PROMETHEUS_PORT: u16 = 9615;
+
+pub const EXPLORER_DEFAULT_PORT: u16 = 4000;
+
+pub const LOG_DEFAULT_MAX_SIZE_MB: u64 = 10;
+pub const LOG_DEFAULT_MAX_FILES: u32 = 5;
+
+pub const MADARA_DOCKER_IMAGE: &str = "ghcr.io/karnotxyz/madara";
@@ -1,67 +1,65 @@
-use bollard::container::{Config, CreateContainerOptions, ListContainersOptions};
+use std::collections::HashMap;
+
+use bollard::container::{
+    Config, CreateContainerOptions, ListContainersOptions, LogsOptions, RemoveContainerOptions, StopContainerOptions,
+    WaitContainerOptions,
+};
+use bollard::errors::Error as BollardError;
+use bollard::exec::{CreateExecOptions, StartExecResults};
 use bollard::image::CreateImageOptions;
 use bollard::models::HostConfig;
 use bollard::Docker;
-use futures_util::TryStreamExt;
+use futures_util::{StreamExt, TryStreamExt};
 
-// TODO: fix unwraps and handle errors
+use crate::utils::errors::DockerError;
+use crate::utils::github::is_offline;
 
 pub async fn run_docker_image(
     image: &str,
     container_name: &str,
     env: Option<Vec<&str>>,
+    cmd: Option<Vec<&str>>,
     host_config: Option<HostConfig>,
-) {
-    is_docker_installed().await;
+) -> Result<(), DockerError> {
+    is_docker_installed().await?;
     log::info!("🐳 Running docker image: {}", image);
-    match pull_and_start_docker_image(image, container_name, env, host_config).await {
-        Ok(..) => {
-            log::debug!("Successfully ran {}", container_name);
-        }
-        Err(err) => {
-            log::error!("Error: {} running image: {}", err, image);
-        }
-    };
+    pull_and_start_docker_image(image, container_name, env, cmd, host_config).await?;
+    log::debug!("Successfully ran {}", container_name);
+    Ok(())
 }
 
-async fn is_docker_installed() {
-    let docker = Docker::connect_with_local_defaults().unwrap();
-    match docker.version().await {
-        Ok(_) => {
-            log::debug!("✅ Docker is installed!");
-            true
-        }
-        Err(_) => {
-            panic!("Please check docker installation, panicking");
-        }
-    };
+fn connect() -> Result<Docker, DockerError> {
+    Docker::connect_with_local_defaults().map_err(DockerError::NotInstalled)
 }
 
-pub async fn container_exists(container_name: &str) -> bool {
-    let docker = Docker::connect_with_local_defaults().unwrap();
+async fn is_docker_installed() -> Result<(), DockerError> {
+    connect()?.version().await.map_err(DockerError::NotInstalled)?;
+    log::debug!("✅ Docker is installed!");
+    Ok(())
+}
+
+pub async fn container_exists(container_name: &str) -> Result<bool, DockerError> {
+    let docker = connect()?;
     let list_container_options = ListContainersOptions { all: true, ..Default::default() };
-    match docker.list_containers::<String>(Some(list_container_options)).await {
-        Ok(containers) => {
-            for container in containers {
-                if let Some(names) = container.names {
-                    if names.contains(&format!("/{}", &container_name.to_string())) {
-                        log::debug!("✅ Container {} exists!", container_name);
-                        return true;
-                    }
-                }
+    let containers = docker
+        .list_containers::<String>(Some(list_container_options))
+        .await
+        .map_err(DockerError::FailedToListContainers)?;
+    for container in containers {
+        if let Some(names) = container.names {
+            if names.contains(&format!("/{}", &container_name.to_string())) {
+                log::debug!("✅ Container {} exists!", container_name);
+                return Ok(true);
             }
-            log::debug!("❌ Container {} does not exist!", container_name);
-            false
-        }
-        Err(e) => {
-            panic!("Failed to fetch containers, panicking {}", e);
         }
     }
+    log::debug!("❌ Container {} does not exist!", container_name);
+    Ok(false)
 }
 
-pub async fn kill_container(container_name: &str) -> eyre::Result<()> {
-    let docker = Docker::connect_with_local_defaults().unwrap();
-    // TODO: handle the error
+pub async fn kill_container(container_name: &str) -> Result<(), DockerError> {
+    let docker = connect()?;
+    // the container may already be stopped, removing it is what matters
     let _ = docker.kill_container::<String>(container_name, None).await;
     docker.remove_container(container_name, None).await?;
     Ok(())
@@ -71,16 +69,28 @@ pub async fn pull_and_start_docker_image(
     image: &str,
     container_name: &str,
     env: Option<Vec<&str>>,
+    cmd: Option<Vec<&str>>,
     host_config: Option<HostConfig>,
-) -> Result<(), Box<dyn std::error::Error + 'static>> {
-    let docker = Docker::connect_with_local_defaults().unwrap();
+) -> Result<(), DockerError> {
+    let docker = connect()?;
+
+    // offline, the image must already be there
+    if !is_offline() || docker.inspect_image(image).await.is_err() {
+        docker
+            .create_image(Some(CreateImageOptions { from_image: image, ..Default::default() }), None, None)
+            .try_collect::<Vec<_>>()
+            .await?;
+    }
 
-    docker
-        .create_image(Some(CreateImageOptions { from_image: image, ..Default::default() }), None, None)
-        .try_collect::<Vec<_>>()
-        .await?;
+    // published ports need to be exposed by the container as well
+    let ports = host_config
+        .as_ref()
+        .and_then(|host_config| host_config.port_bindings.as_ref())
+        .map(|port_bindings| port_bindings.keys().cloned().collect::<Vec<_>>());
+    let exposed_ports = ports.as_ref().map(|ports| ports.iter().map(|port| (port.as_str(), HashMap::new())).collect());
 
-    let config = Config { image: Some(image), tty: Some(true), env, host_config, ..Default::default() };
+    let config =
+        Config { image: Some(image), tty: Some(true), env, cmd, exposed_ports, host_config, ..Default::default() };
 
     let container_option = Some(CreateContainerOptions { name: container_name, ..Default::default() });
 
@@ -90,3 +100,99 @@ pub async fn pull_and_start_docker_image(
 
     Ok(())
 }
+
+pub async fn is_container_running(container_name: &str) -> Result<bool, DockerError> {
+    let docker = connect()?;
+    match docker.inspect_container(container_name, None).await {
+        Ok(container) => Ok(container.state.and_then(|state| state.running).unwrap_or(false)),
+        Err(BollardError::DockerResponseServerError { status_code: 404, .. }) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Host port the port of the container, e.g. "4000/tcp", is published on
+pub async fn get_published_port(container_name: &str, container_port: &str) -> Result<Option<u16>, DockerError> {
+    let docker = connect()?;
+    let container = docker.inspect_container(container_name, None).await?;
+    let port = container
+        .host_config
+        .and_then(|host_config| host_config.port_bindings)
+        .and_then(|port_bindings| port_bindings.get(container_port).cloned().flatten())
+        .and_then(|bindings| bindings.into_iter().find_map(|binding| binding.host_port?.parse().ok()));
+    Ok(port)
+}
+
+/// Stops the container, killing it if it is still running after `timeout_secs`, and removes it
+pub async fn stop_container(container_name: &str, timeout_secs: u64) -> Result<(), DockerError> {
+    let docker = connect()?;
+    docker.stop_container(container_name, Some(StopContainerOptions { t: timeout_secs as i64 })).await?;
+    docker.remove_container(container_name, None).await?;
+    Ok(())
+}
+
+/// Runs the image until the container exits and removes the container afterwards
+pub async fn run_docker_image_to_completion(
+    image: &str,
+    container_name: &str,
+    cmd: Vec<&str>,
+    host_config: Option<HostConfig>,
+) -> Result<(), DockerError> {
+    run_docker_image(image, container_name, None, Some(cmd), host_config).await?;
+
+    let docker = connect()?;
+    let result =
+        docker.wait_container(container_name, None::<WaitContainerOptions<String>>).try_collect::<Vec<_>>().await;
+    docker.remove_container(container_name, Some(RemoveContainerOptions { force: true, ..Default::default() })).await?;
+
+    result?;
+    Ok(())
+}
+
+/// Runs the command inside the running container and returns its output
+pub async fn exec_in_container(container_name: &str, cmd: Vec<&str>) -> Result<String, DockerError> {
+    let docker = connect()?;
+    let exec_options =
+        CreateExecOptions { cmd: Some(cmd), attach_stdout: Some(true), attach_stderr: Some(true), ..Default::default() };
+    let exec = docker.create_exec(container_name, exec_options).await?;
+
+    let mut output = String::new();
+    if let StartExecResults::Attached { output: mut stream, .. } = docker.start_exec(&exec.id, None).await? {
+        while let Some(log) = stream.next().await {
+            output.push_str(&log?.to_string());
+        }
+    }
+
+    Ok(output)
+}
+
+/// Streams the logs of the container, prefixed with their timestamp, line by line. Logs since the
+/// unix timestamp `since` are read, all of them if it's 0, and new ones are followed if `follow`
+/// is set, until the container stops or `on_line` returns false.
+pub async fn stream_container_logs(
+    container_name: &str,
+    follow: bool,
+    since: i64,
+    mut on_line: impl FnMut(&str) -> bool,
+) -> Result<(), DockerError> {
+    let docker = connect()?;
+    let options =
+        LogsOptions::<String> { follow, stdout: true, stderr: true, since, timestamps: true, ..Default::default() };
+    let mut logs = docker.logs(container_name, Some(options));
+
+    // containers run with a tty, so their output isn't split in lines
+    let mut pending = Vec::new();
+    while let Some(output) = logs.next().await {
+        pending.extend_from_slice(&output?.into_bytes());
+        while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            if !on_line(String::from_utf8_lossy(&line).trim_end()) {
+                return Ok(());
+            }
+        }
+    }
+    if !pending.is_empty() {
+        on_line(String::from_utf8_lossy(&pending).trim_end());
+    }
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
 use std::ffi::OsString;
+
+use inquire::InquireError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -13,6 +15,16 @@ pub enum GithubError {
     CommandExecutionFailed(#[from] std::io::Error),
     #[error("Unable to fetch remote")]
     RemoteFetchFailed(#[from] git2::Error),
+    #[error("Failed to get tags from Github: {0}")]
+    FailedToGetTags(reqwest::Error),
+    #[error("Not a GitHub repository: {0}")]
+    InvalidRepoUrl(String),
+    #[error("No branch, tag or commit {0} in {1}")]
+    ReferenceNotFound(String, String),
+    #[error("Can't reach {0} in offline mode")]
+    Offline(String),
+    #[error("GitHub API rate limit exceeded, set GITHUB_TOKEN or use --offline")]
+    RateLimited,
 }
 
 #[derive(Debug, Error)]
@@ -29,6 +41,14 @@ pub enum MadaraError {
     FailedToGetDAConfig,
     #[error("Unable to fetch remote")]
     FailedToConvertToString(OsString),
+    #[error("Failed to start node: {0}")]
+    FailedToStartNode(#[from] ProcessError),
+    #[error("Failed to run node container: {0}")]
+    FailedToRunContainer(#[from] DockerError),
+    #[error("Failed to parse DA config: {0}")]
+    FailedToParseDAConfig(#[from] serde_json::Error),
+    #[error("Failed to write DA config: {0}")]
+    FailedToWriteDAConfig(#[from] KeystoreError),
 }
 
 #[derive(Debug, Error)]
@@ -37,4 +57,80 @@ pub enum TomlError {
     FailedToReadFile(#[from] std::io::Error),
     #[error("Failed to parse toml file: {0}")]
     FailedToParseToml(#[from] toml::de::Error),
+    #[error("Failed to serialize to toml: {0}")]
+    FailedToSerializeToml(#[from] toml::ser::Error),
+    #[error("Missing field `{0}`")]
+    MissingField(String),
+    #[error("Failed to back up the config: {0}")]
+    FailedToBackupConfig(std::io::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ProcessError {
+    #[error("Failed to access node state: {0}")]
+    FailedToAccessState(#[from] std::io::Error),
+    #[error("Failed to parse node state: {0}")]
+    FailedToParseState(#[from] serde_json::Error),
+    #[error("Failed to send signal to process {0}: {1}")]
+    FailedToSignal(u32, nix::Error),
+    #[error("App chain {0} is not running")]
+    NotRunning(String),
+    #[error("Failed to manage node container: {0}")]
+    FailedToManageContainer(#[from] DockerError),
+}
+
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("Failed to get the keystore password: {0}")]
+    FailedToGetPassword(#[from] InquireError),
+    #[error("Failed to read the password file: {0}")]
+    FailedToReadPasswordFile(std::io::Error),
+    #[error("The keystore password can't be empty")]
+    EmptyPassword,
+    #[error("Failed to access the keystore: {0}")]
+    FailedToAccessKeystore(#[from] std::io::Error),
+    #[error("Failed to parse the keystore: {0}")]
+    FailedToParseKeystore(#[from] serde_json::Error),
+    #[error("Unsupported keystore: {0}")]
+    UnsupportedKeystore(String),
+    #[error("Failed to decrypt the keystore, check the password")]
+    WrongPassword,
+    #[error("Failed to encrypt the keystore")]
+    FailedToEncrypt,
+    #[error("Invalid key in the keystore")]
+    InvalidKey,
+    #[error("Failed to use the Ethereum keystore: {0}")]
+    FailedToUseEthereumKeystore(#[from] ethers::signers::WalletError),
+}
+
+#[derive(Debug, Error)]
+pub enum HealthError {
+    #[error("Failed to get node state: {0}")]
+    FailedToGetNodeState(#[from] ProcessError),
+    #[error("Failed to create the HTTP client: {0}")]
+    FailedToCreateClient(#[from] reqwest::Error),
+    #[error("App chain {0} is not running")]
+    NotRunning(String),
+    #[error("App chain {0} stopped while starting, check `madara logs -n {0}`")]
+    Stopped(String),
+    #[error("Node RPC at {0} is unreachable: {1}")]
+    RpcUnreachable(String, reqwest::Error),
+    #[error("Node RPC failed to answer {0}: {1}")]
+    RpcError(String, String),
+    #[error("Invalid node response to {0}: {1}")]
+    InvalidResponse(String, String),
+    #[error("Node metrics at {0} are unreachable: {1}")]
+    PrometheusUnreachable(String, reqwest::Error),
+    #[error("App chain {0} is not ready after {1}s: {2}")]
+    NotReady(String, u64, String),
+}
+
+#[derive(Debug, Error)]
+pub enum DockerError {
+    #[error("Docker is not installed or not running: {0}")]
+    NotInstalled(bollard::errors::Error),
+    #[error("Failed to fetch containers: {0}")]
+    FailedToListContainers(bollard::errors::Error),
+    #[error("Docker request failed: {0}")]
+    RequestFailed(#[from] bollard::errors::Error),
 }
@@ -1,52 +1,190 @@
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::{env, fs};
 
 use crate::utils::cmd::{execute_cmd, execute_cmd_stdio};
 use git2::Repository;
-use reqwest::Client;
+use reqwest::{Client, Response, StatusCode};
 use serde::Deserialize;
 
 use crate::utils::errors::GithubError;
+use crate::utils::madara::GITHUB_BASE_URL;
 pub const GITHUB_API_BASE_URL: &str = "https://api.github.com";
+pub const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
+pub const OFFLINE_ENV: &str = "MADARA_OFFLINE";
 
-use crate::utils::constants::MADARA_BRANCH_NAME;
+/// Set with `--offline`
+static OFFLINE: OnceLock<bool> = OnceLock::new();
 
 #[derive(Debug, Deserialize)]
-struct Commit {
-    sha: String,
+pub struct Commit {
+    pub sha: String,
 }
 
-pub async fn get_latest_commit_hash(org: &str, repo: &str, branch: &str) -> Result<String, GithubError> {
-    let github_api_url = format!("{}/repos/{}/{}/commits/{}", GITHUB_API_BASE_URL, org, repo, branch);
+#[derive(Debug, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    pub commit: Commit,
+}
+
+/// Never reach GitHub or pull images, only use the local clones and caches
+pub fn set_offline(offline: bool) -> Result<(), std::io::Error> {
+    OFFLINE
+        .set(offline)
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Offline mode is already set"))
+}
+
+/// Offline mode is enabled with `--offline` or `MADARA_OFFLINE=1`
+pub fn is_offline() -> bool {
+    OFFLINE.get().copied().unwrap_or(false)
+        || env::var(OFFLINE_ENV).is_ok_and(|offline| offline == "1" || offline.eq_ignore_ascii_case("true"))
+}
+
+/// Path of the repository if the URL points to a local clone or mirror, e.g. "/srv/madara" or
+/// "file:///srv/madara"
+pub fn get_local_repo_path(url: &str) -> Option<PathBuf> {
+    match url.strip_prefix("file://") {
+        Some(path) => Some(PathBuf::from(path)),
+        None if !url.contains("://") && !url.starts_with("git@") => Some(PathBuf::from(url)),
+        None => None,
+    }
+}
+
+/// Makes local repository paths absolute, so that they don't depend on the current directory
+pub fn normalize_repo_url(url: &str) -> Result<String, GithubError> {
+    match get_local_repo_path(url) {
+        Some(path) if path.is_relative() => Ok(env::current_dir()?.join(path).to_string_lossy().to_string()),
+        _ => Ok(url.to_string()),
+    }
+}
 
-    let client = Client::new();
-    let response = client.get(github_api_url).header("User-Agent", "reqwest").send().await;
+/// Splits a GitHub repository URL into its org and name, e.g. "https://github.com/karnotxyz/madara"
+pub fn parse_github_repo(url: &str) -> Result<(String, String), GithubError> {
+    let path = url.strip_prefix(GITHUB_BASE_URL).and_then(|path| path.strip_prefix('/'));
+    let parts: Vec<&str> = match path {
+        Some(path) => path.trim_end_matches('/').trim_end_matches(".git").split('/').collect(),
+        None => vec![],
+    };
+    match parts.as_slice() {
+        [org, repo] if !org.is_empty() && !repo.is_empty() => Ok((org.to_string(), repo.to_string())),
+        _ => Err(GithubError::InvalidRepoUrl(url.to_string())),
+    }
+}
+
+/// Resolves a branch, tag or commit of the repository to a commit hash
+pub async fn get_commit_hash(org: &str, repo: &str, reference: &str) -> Result<String, GithubError> {
+    let github_api_url = format!("{}/repos/{}/{}/commits/{}", GITHUB_API_BASE_URL, org, repo, reference);
+
+    let response = github_get(&github_api_url).await?;
+    if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::UNPROCESSABLE_ENTITY) {
+        return Err(GithubError::ReferenceNotFound(reference.to_string(), format!("{}/{}", org, repo)));
+    }
+
+    Ok(response.error_for_status()?.json::<Commit>().await?.sha)
+}
+
+pub async fn is_tag(org: &str, repo: &str, name: &str) -> Result<bool, GithubError> {
+    let github_api_url = format!("{}/repos/{}/{}/git/ref/tags/{}", GITHUB_API_BASE_URL, org, repo, name);
 
-    match response {
-        Ok(response) => match response.json::<Commit>().await {
-            Ok(latest_commit) => Ok(latest_commit.sha.clone()),
-            Err(err) => Err(GithubError::FailedToGetCommits(err)),
-        },
-        Err(err) => Err(GithubError::FailedToGetCommits(err)),
+    let response = github_get(&github_api_url).await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(false);
     }
+    response.error_for_status()?;
+
+    Ok(true)
+}
+
+/// Latest tags of the repository, most recent first
+pub async fn get_tags(org: &str, repo: &str) -> Result<Vec<Tag>, GithubError> {
+    let github_api_url = format!("{}/repos/{}/{}/tags?per_page=100", GITHUB_API_BASE_URL, org, repo);
+
+    let response = github_get(&github_api_url).await?.error_for_status().map_err(GithubError::FailedToGetTags)?;
+    response.json().await.map_err(GithubError::FailedToGetTags)
 }
 
+/// Resolves a branch, tag or commit of a local repository to a commit hash. Branches which were
+/// only fetched are looked up in the remote tracking branches.
+pub fn get_local_commit_hash(path: &Path, reference: &str) -> Result<String, GithubError> {
+    let repo = Repository::open(path)?;
+    [reference.to_string(), format!("origin/{}", reference)]
+        .iter()
+        .find_map(|candidate| repo.revparse_single(&format!("{}^{{commit}}", candidate)).ok())
+        .map(|commit| commit.id().to_string())
+        .ok_or_else(|| GithubError::ReferenceNotFound(reference.to_string(), path.display().to_string()))
+}
+
+pub fn is_local_tag(path: &Path, name: &str) -> Result<bool, GithubError> {
+    Ok(Repository::open(path)?.find_reference(&format!("refs/tags/{}", name)).is_ok())
+}
+
+/// Tags of a local repository, most recent first
+pub fn get_local_tags(path: &Path) -> Result<Vec<Tag>, GithubError> {
+    let repo = Repository::open(path)?;
+    let mut tags = Vec::new();
+    for name in repo.tag_names(None)?.iter().flatten() {
+        let commit = repo.revparse_single(&format!("refs/tags/{}", name))?.peel_to_commit()?;
+        let tag = Tag { name: name.to_string(), commit: Commit { sha: commit.id().to_string() } };
+        tags.push((commit.time().seconds(), tag));
+    }
+    tags.sort_by(|(time, _), (other_time, _)| other_time.cmp(time));
+
+    Ok(tags.into_iter().map(|(_, tag)| tag).collect())
+}
+
+/// Whether the path holds a clone of the repository at `url`
+pub fn is_clone_of(path: &Path, url: &str) -> bool {
+    let Ok(repo) = Repository::open(path) else {
+        return false;
+    };
+    let remote_url = repo.find_remote("origin").ok().and_then(|remote| remote.url().map(|url| url.to_string()));
+    repo.is_empty() == Ok(false) && remote_url.as_deref() == Some(url)
+}
+
+/// Calls the GitHub API, authenticated with `GITHUB_TOKEN` when it is set
+async fn github_get(url: &str) -> Result<Response, GithubError> {
+    if is_offline() {
+        return Err(GithubError::Offline(url.to_string()));
+    }
+
+    let mut request = Client::new().get(url).header("User-Agent", "reqwest");
+    if let Some(token) = env::var(GITHUB_TOKEN_ENV).ok().filter(|token| !token.is_empty()) {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await?;
+
+    let rate_limited = matches!(response.status(), StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS)
+        && response.headers().get("x-ratelimit-remaining").is_some_and(|remaining| remaining == "0");
+    if rate_limited {
+        return Err(GithubError::RateLimited);
+    }
+
+    Ok(response)
+}
+
+/// Clones the repository, or reuses an existing clone, and checks out the commit. Only fetches
+/// when the commit isn't in the clone yet.
 pub fn git_clone(url: &str, path: &PathBuf, commit: Option<&str>) -> Result<(), GithubError> {
-    if let Ok(repo) = Repository::open(path) {
-        // Check if the repository is valid
-        if repo.is_empty() == Ok(false) {
-            let remote = repo.find_remote("origin")?;
-            if let Some(remote_url) = remote.url() {
-                if remote_url == url {
-                    if let Some(commit) = commit {
-                        execute_cmd("git", &["fetch"], path)?;
-                        execute_cmd_stdio("git", &["checkout", commit], path, Stdio::null(), Stdio::null())?;
-                    }
-                    return Ok(());
+    // local mirrors can still be fetched from offline
+    let can_fetch = !is_offline() || get_local_repo_path(url).is_some();
+
+    if is_clone_of(path, url) {
+        if let Some(commit) = commit {
+            let repo = Repository::open(path)?;
+            if repo.revparse_single(&format!("{}^{{commit}}", commit)).is_err() {
+                if !can_fetch {
+                    return Err(GithubError::Offline(url.to_string()));
                 }
+                execute_cmd("git", &["fetch"], path)?;
             }
+            execute_cmd_stdio("git", &["checkout", commit], path, Stdio::null(), Stdio::null())?;
         }
+        return Ok(());
+    }
+
+    if !can_fetch {
+        return Err(GithubError::Offline(url.to_string()));
     }
 
     if path.exists() {
@@ -56,14 +194,7 @@ pub fn git_clone(url: &str, path: &PathBuf, commit: Option<&str>) -> Result<(),
     }
 
     let mut cmd = Command::new("git");
-    cmd.arg("clone")
-        .arg("--progress")
-        .arg(url)
-        .arg(path)
-        .arg("--branch")
-        .arg(MADARA_BRANCH_NAME)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit());
+    cmd.arg("clone").arg("--progress").arg(url).arg(path).stdout(Stdio::inherit()).stderr(Stdio::inherit());
 
     let output = cmd.output()?;
 
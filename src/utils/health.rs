@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::time::Instant;
+
+use crate::utils::errors::HealthError;
+use crate::utils::process::NodeState;
+
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const HEALTH_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<serde_json::Value>,
+}
+
+/// What the node reports through its RPC and Prometheus endpoints
+#[derive(Debug)]
+pub struct NodeHealth {
+    pub block_number: u64,
+    pub chain_id: String,
+    /// Best block in the Prometheus metrics, unset when Prometheus is disabled
+    pub best_block: Option<u64>,
+}
+
+pub fn get_rpc_url(state: &NodeState) -> String {
+    format!("http://localhost:{}", state.rpc_port)
+}
+
+fn get_metrics_url(state: &NodeState) -> String {
+    format!("http://localhost:{}/metrics", state.prometheus_port)
+}
+
+/// Queries the Starknet JSON-RPC and the Prometheus metrics of the node once
+pub async fn get_node_health(state: &NodeState) -> Result<NodeHealth, HealthError> {
+    let client = Client::builder().timeout(HEALTH_REQUEST_TIMEOUT).build()?;
+    let rpc_url = get_rpc_url(state);
+
+    let block_number = call_rpc::<u64>(&client, &rpc_url, "starknet_blockNumber").await?;
+    let chain_id = call_rpc::<String>(&client, &rpc_url, "starknet_chainId").await?;
+    let best_block = match state.madara_flags.iter().any(|flag| flag == "--no-prometheus") {
+        true => None,
+        false => Some(get_best_block(&client, &get_metrics_url(state)).await?),
+    };
+
+    Ok(NodeHealth { block_number, chain_id: decode_chain_id(&chain_id), best_block })
+}
+
+/// Polls the node until it produces a new block. Fails if it doesn't within `timeout` or if
+/// the node stops.
+pub async fn wait_for_blocks(app: &str, state: &NodeState, timeout: Duration) -> Result<NodeHealth, HealthError> {
+    let deadline = Instant::now() + timeout;
+    let mut first_block = None;
+
+    loop {
+        if !state.is_running().await? {
+            return Err(HealthError::Stopped(app.to_string()));
+        }
+
+        let last_error = match get_node_health(state).await {
+            Ok(health) if first_block.is_some_and(|first_block| health.block_number > first_block) => {
+                return Ok(health);
+            }
+            Ok(health) => {
+                first_block.get_or_insert(health.block_number);
+                format!("no new block after block {}", health.block_number)
+            }
+            Err(err) => err.to_string(),
+        };
+
+        if Instant::now() >= deadline {
+            return Err(HealthError::NotReady(app.to_string(), timeout.as_secs(), last_error));
+        }
+        tokio::time::sleep_until(deadline.min(Instant::now() + HEALTH_POLL_INTERVAL)).await;
+    }
+}
+
+async fn call_rpc<T: DeserializeOwned>(client: &Client, rpc_url: &str, method: &str) -> Result<T, HealthError> {
+    let response = client
+        .post(rpc_url)
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": [] }))
+        .send()
+        .await
+        .map_err(|err| HealthError::RpcUnreachable(rpc_url.to_string(), err))?
+        .json::<RpcResponse<T>>()
+        .await
+        .map_err(|err| HealthError::InvalidResponse(method.to_string(), err.to_string()))?;
+
+    match (response.result, response.error) {
+        (Some(result), _) => Ok(result),
+        (None, Some(error)) => Err(HealthError::RpcError(method.to_string(), error.to_string())),
+        (None, None) => Err(HealthError::InvalidResponse(method.to_string(), "empty response".to_string())),
+    }
+}
+
+/// Reads `substrate_block_height{status="best"}` from the Prometheus metrics
+async fn get_best_block(client: &Client, metrics_url: &str) -> Result<u64, HealthError> {
+    let metrics = client
+        .get(metrics_url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| HealthError::PrometheusUnreachable(metrics_url.to_string(), err))?
+        .text()
+        .await
+        .map_err(|err| HealthError::PrometheusUnreachable(metrics_url.to_string(), err))?;
+
+    metrics
+        .lines()
+        .filter(|line| line.starts_with("substrate_block_height{") && line.contains("status=\"best\""))
+        .find_map(|line| line.split_whitespace().last()?.parse().ok())
+        .ok_or(HealthError::InvalidResponse("metrics".to_string(), "no best block height".to_string()))
+}
+
+/// Chain ids are short strings encoded as a felt, e.g. "0x4d4144415241" for "MADARA"
+fn decode_chain_id(chain_id: &str) -> String {
+    let decoded = hex::decode(chain_id.trim_start_matches("0x")).ok().and_then(|bytes| String::from_utf8(bytes).ok());
+    match decoded {
+        Some(decoded) if !decoded.is_empty() && decoded.chars().all(|c| c.is_ascii_graphic() || c == ' ') => decoded,
+        _ => chain_id.to_string(),
+    }
+}
@@ -0,0 +1,255 @@
+use std::fs::{self, OpenOptions};
+use std::io::{Error, ErrorKind, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, io};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use crypto_secretbox::aead::{Aead, KeyInit};
+use crypto_secretbox::{Nonce, XSalsa20Poly1305};
+use ethers::signers::LocalWallet;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::prompt::{get_new_password_input, get_password_input, KEYSTORE_PASSWORD};
+use crate::utils::constants::{APP_KEYSTORE_NAME, APP_UNLOCKED_DA_CONFIG_NAME};
+use crate::utils::errors::KeystoreError;
+use crate::utils::paths::get_app_home;
+
+pub const KEYSTORE_PASSWORD_ENV: &str = "MADARA_KEYSTORE_PASSWORD";
+
+/// File set with `--password-file`
+static PASSWORD_FILE: OnceLock<PathBuf> = OnceLock::new();
+/// Password unlocked during this run, so that it's only asked once
+static PASSWORD: OnceLock<String> = OnceLock::new();
+
+// Encryption of the substrate JSON keystore, as written by polkadot-js
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SALT_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 24;
+const SCRYPT_PARAMS_LENGTH: usize = SALT_LENGTH + 12;
+const SR25519_SEED_LENGTH: usize = 32;
+const SR25519_CONTENT: [&str; 2] = ["seed", "sr25519"];
+const CELESTIA_CONTENT: [&str; 2] = ["auth-token", "celestia"];
+
+/// Keystore of a secret other than an Ethereum key. It has the layout and encryption of the
+/// substrate JSON keystore, but its content is an sr25519 seed or a Celestia auth token instead
+/// of a pkcs8 encoded key, so polkadot-js and subkey can't read it.
+#[derive(Debug, Serialize, Deserialize)]
+struct SeedKeystore {
+    encoded: String,
+    encoding: SeedKeystoreEncoding,
+    address: String,
+    #[serde(default)]
+    meta: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SeedKeystoreEncoding {
+    content: Vec<String>,
+    #[serde(rename = "type")]
+    kind: Vec<String>,
+    version: String,
+}
+
+/// Reads the keystore password from a file instead of prompting for it
+pub fn set_password_file(password_file: PathBuf) -> Result<(), Error> {
+    PASSWORD_FILE.set(password_file).map_err(|_| Error::new(ErrorKind::AlreadyExists, "Password file is already set"))
+}
+
+/// Password of the app chain keystore, taken from `MADARA_KEYSTORE_PASSWORD`, the password file
+/// or a prompt. New passwords are asked twice when prompted.
+pub fn get_keystore_password(new: bool) -> Result<String, KeystoreError> {
+    if let Some(password) = PASSWORD.get() {
+        return Ok(password.clone());
+    }
+
+    let env_password = env::var(KEYSTORE_PASSWORD_ENV).ok().filter(|password| !password.is_empty());
+    let password = match (env_password, PASSWORD_FILE.get()) {
+        (Some(password), _) => password,
+        (None, Some(password_file)) => {
+            let password = fs::read_to_string(password_file).map_err(KeystoreError::FailedToReadPasswordFile)?;
+            password.trim_end_matches(['\r', '\n']).to_string()
+        }
+        (None, None) if new => get_new_password_input(KEYSTORE_PASSWORD, "Enter a password for the keystore:")?,
+        (None, None) => get_password_input(KEYSTORE_PASSWORD, "Enter the password of the keystore:")?,
+    };
+    if password.is_empty() {
+        return Err(KeystoreError::EmptyPassword);
+    }
+
+    Ok(PASSWORD.get_or_init(|| password).clone())
+}
+
+pub fn get_keystore_path(app: &str) -> Result<PathBuf, KeystoreError> {
+    Ok(get_app_home(app)?.join(APP_KEYSTORE_NAME))
+}
+
+/// Encrypts an sr25519 seed with scrypt and xsalsa20-poly1305, like the substrate JSON keystore.
+/// The seed is stored rather than the pkcs8 encoded key, which loses the seed, as Madara only
+/// takes a seed or a secret phrase.
+pub fn write_sr25519_keystore(app: &str, seed: &[u8], address: &str, password: &str) -> Result<(), KeystoreError> {
+    write_secret_keystore(app, SR25519_CONTENT, seed, address, password)
+}
+
+/// Decrypts the sr25519 seed of the keystore
+pub fn read_sr25519_keystore(app: &str, password: &str) -> Result<Vec<u8>, KeystoreError> {
+    let seed = read_secret_keystore(app, SR25519_CONTENT, password)?;
+    if seed.len() != SR25519_SEED_LENGTH {
+        return Err(KeystoreError::InvalidKey);
+    }
+
+    Ok(seed)
+}
+
+/// Encrypts the auth token of a Celestia light node like an sr25519 seed
+pub fn write_celestia_keystore(app: &str, auth_token: &str, password: &str) -> Result<(), KeystoreError> {
+    write_secret_keystore(app, CELESTIA_CONTENT, auth_token.as_bytes(), "", password)
+}
+
+/// Decrypts the auth token of the Celestia light node
+pub fn read_celestia_keystore(app: &str, password: &str) -> Result<String, KeystoreError> {
+    String::from_utf8(read_secret_keystore(app, CELESTIA_CONTENT, password)?).map_err(|_| KeystoreError::InvalidKey)
+}
+
+/// Encrypts an Ethereum private key in the Web3 Secret Storage format
+pub fn write_ethereum_keystore(app: &str, private_key: &[u8], password: &str) -> Result<(), KeystoreError> {
+    let keystore_path = get_keystore_path(app)?;
+    let keystore_dir = keystore_path.parent().ok_or(KeystoreError::InvalidKey)?;
+    LocalWallet::encrypt_keystore(
+        keystore_dir,
+        &mut rand::thread_rng(),
+        private_key,
+        password,
+        Some(APP_KEYSTORE_NAME),
+    )?;
+    fs::set_permissions(&keystore_path, fs::Permissions::from_mode(0o600))?;
+
+    Ok(())
+}
+
+/// Copies an existing Web3 Secret Storage file, which keeps its password
+pub fn import_ethereum_keystore(app: &str, keystore: &Path) -> Result<(), KeystoreError> {
+    write_private_file(&get_keystore_path(app)?, &fs::read(keystore)?)?;
+    Ok(())
+}
+
+pub fn read_ethereum_keystore(app: &str, password: &str) -> Result<LocalWallet, KeystoreError> {
+    Ok(LocalWallet::decrypt_keystore(get_keystore_path(app)?, password)?)
+}
+
+/// Path of the DA config with the secrets injected, in the app home and only readable by the
+/// current user
+pub fn get_unlocked_da_config_path(app: &str) -> Result<PathBuf, io::Error> {
+    Ok(get_app_home(app)?.join(APP_UNLOCKED_DA_CONFIG_NAME))
+}
+
+/// Writes the DA config with the secrets injected for Madara to read at launch.
+/// It is removed when the app chain is stopped, or found to have exited.
+pub fn write_unlocked_da_config(app: &str, da_config: &serde_json::Value) -> Result<PathBuf, KeystoreError> {
+    let path = get_unlocked_da_config_path(app)?;
+    write_private_file(&path, serde_json::to_string(da_config)?.as_bytes())?;
+    Ok(path)
+}
+
+pub fn remove_unlocked_da_config(app: &str) -> Result<(), io::Error> {
+    match fs::remove_file(get_unlocked_da_config_path(app)?) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Encrypts a secret with scrypt and xsalsa20-poly1305, like the substrate JSON keystore
+fn write_secret_keystore(
+    app: &str,
+    content: [&str; 2],
+    secret: &[u8],
+    address: &str,
+    password: &str,
+) -> Result<(), KeystoreError> {
+    let mut salt = [0u8; SALT_LENGTH];
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(password, &salt, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)?;
+    let encrypted = XSalsa20Poly1305::new(&key.into())
+        .encrypt(&Nonce::from(nonce), secret)
+        .map_err(|_| KeystoreError::FailedToEncrypt)?;
+
+    let n = 1u32 << SCRYPT_LOG_N;
+    let encoded =
+        [&salt[..], &n.to_le_bytes(), &SCRYPT_P.to_le_bytes(), &SCRYPT_R.to_le_bytes(), &nonce, &encrypted].concat();
+
+    let when_created = SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_millis()).unwrap_or_default();
+    let keystore = SeedKeystore {
+        encoded: BASE64.encode(encoded),
+        encoding: SeedKeystoreEncoding {
+            content: content.map(str::to_string).to_vec(),
+            kind: vec!["scrypt".to_string(), "xsalsa20-poly1305".to_string()],
+            version: "3".to_string(),
+        },
+        address: address.to_string(),
+        meta: serde_json::json!({ "name": app, "whenCreated": when_created }),
+    };
+
+    write_private_file(&get_keystore_path(app)?, serde_json::to_string(&keystore)?.as_bytes())?;
+
+    Ok(())
+}
+
+/// Decrypts a secret written by `write_secret_keystore`, which must hold the expected content
+fn read_secret_keystore(app: &str, content: [&str; 2], password: &str) -> Result<Vec<u8>, KeystoreError> {
+    let keystore: SeedKeystore = serde_json::from_str(&fs::read_to_string(get_keystore_path(app)?)?)?;
+    if keystore.encoding.content != content || keystore.encoding.kind != ["scrypt", "xsalsa20-poly1305"] {
+        return Err(KeystoreError::UnsupportedKeystore(format!(
+            "{} {}",
+            keystore.encoding.content.join("/"),
+            keystore.encoding.kind.join("/")
+        )));
+    }
+
+    let encoded = BASE64.decode(&keystore.encoded).map_err(|_| KeystoreError::InvalidKey)?;
+    if encoded.len() < SCRYPT_PARAMS_LENGTH + NONCE_LENGTH {
+        return Err(KeystoreError::InvalidKey);
+    }
+    let (params, encrypted) = encoded.split_at(SCRYPT_PARAMS_LENGTH);
+    let (salt, params) = params.split_at(SALT_LENGTH);
+    let read_u32 = |offset: usize| u32::from_le_bytes([0, 1, 2, 3].map(|i| params[offset + i]));
+    let (n, p, r) = (read_u32(0), read_u32(4), read_u32(8));
+    if !n.is_power_of_two() {
+        return Err(KeystoreError::InvalidKey);
+    }
+
+    let key = derive_key(password, salt, n.trailing_zeros() as u8, r, p)?;
+    let (nonce, encrypted) = encrypted.split_at(NONCE_LENGTH);
+    let nonce = <[u8; NONCE_LENGTH]>::try_from(nonce).map_err(|_| KeystoreError::InvalidKey)?;
+    XSalsa20Poly1305::new(&key.into()).decrypt(&Nonce::from(nonce), encrypted).map_err(|_| KeystoreError::WrongPassword)
+}
+
+fn derive_key(password: &str, salt: &[u8], log_n: u8, r: u32, p: u32) -> Result<[u8; 32], KeystoreError> {
+    let params = scrypt::Params::new(log_n, r, p).map_err(|_| KeystoreError::InvalidKey)?;
+    // polkadot-js derives 64 bytes and uses the first half as the key
+    let mut derived = [0u8; 64];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut derived).map_err(|_| KeystoreError::InvalidKey)?;
+
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&derived[..32]);
+    Ok(key)
+}
+
+/// Creates the file with 0600 permissions. An existing file is replaced rather than reused, so
+/// that a file planted in a shared directory can't keep looser permissions.
+pub fn write_private_file(path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+        _ => (),
+    }
+    let mut file = OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)?;
+    file.write_all(contents)
+}
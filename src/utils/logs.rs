@@ -0,0 +1,118 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::app::config::LogsConfig;
+use crate::utils::constants::{APP_LOGS_DIR, APP_NODE_LOG_NAME};
+use crate::utils::paths::get_app_home;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Log file rotated by size. Rotated files are numbered from the newest, e.g. "madara.log.1",
+/// and only `max_files` of them are kept.
+pub struct RotatingLog {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: u32,
+}
+
+impl RotatingLog {
+    pub fn open(path: &Path, config: &LogsConfig) -> Result<Self, io::Error> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+
+        Ok(RotatingLog {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size: config.max_size_mb.max(1) * BYTES_PER_MB,
+            max_files: config.max_files,
+        })
+    }
+
+    /// Writes the line prefixed with the current time, rotating the file once it's too large
+    pub fn write_line(&mut self, line: &[u8]) -> Result<(), io::Error> {
+        let timestamp = format!("{} ", humantime::format_rfc3339_seconds(SystemTime::now()));
+        let entry = [timestamp.as_bytes(), line, b"\n"].concat();
+        self.file.write_all(&entry)?;
+        self.size += entry.len() as u64;
+
+        if self.size >= self.max_size {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<(), io::Error> {
+        remove_if_exists(&get_rotated_path(&self.path, self.max_files))?;
+        for index in (1..self.max_files).rev() {
+            let rotated_path = get_rotated_path(&self.path, index);
+            if rotated_path.exists() {
+                fs::rename(&rotated_path, get_rotated_path(&self.path, index + 1))?;
+            }
+        }
+        match self.max_files {
+            0 => remove_if_exists(&self.path)?,
+            _ => fs::rename(&self.path, get_rotated_path(&self.path, 1))?,
+        }
+
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+pub fn get_logs_dir(app: &str) -> Result<PathBuf, io::Error> {
+    Ok(get_app_home(app)?.join(APP_LOGS_DIR))
+}
+
+pub fn get_node_log_path(app: &str) -> Result<PathBuf, io::Error> {
+    Ok(get_logs_dir(app)?.join(APP_NODE_LOG_NAME))
+}
+
+fn get_rotated_path(path: &Path, index: u32) -> PathBuf {
+    let mut rotated_path = path.as_os_str().to_owned();
+    rotated_path.push(format!(".{}", index));
+    PathBuf::from(rotated_path)
+}
+
+/// The log file and its rotated files, from the oldest to the newest
+pub fn get_log_files(path: &Path) -> Vec<PathBuf> {
+    let mut log_files: Vec<PathBuf> =
+        (1..).map(|index| get_rotated_path(path, index)).take_while(|rotated_path| rotated_path.exists()).collect();
+    log_files.reverse();
+    if path.exists() {
+        log_files.push(path.to_path_buf());
+    }
+    log_files
+}
+
+/// Writes the lines read from `input` to the rotating log until the input is closed, which
+/// happens when the node exits
+pub fn write_logs(input: impl BufRead, path: &Path, config: &LogsConfig) -> Result<(), io::Error> {
+    let mut log = RotatingLog::open(path, config)?;
+    for line in input.split(b'\n') {
+        let line = line?;
+        log.write_line(line.strip_suffix(b"\r").unwrap_or(&line))?;
+    }
+    Ok(())
+}
+
+/// Time a line was logged at, read from its timestamp prefix
+pub fn get_line_time(line: &str) -> Option<SystemTime> {
+    let (timestamp, _) = line.split_once(' ')?;
+    humantime::parse_rfc3339_weak(timestamp).ok()
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), io::Error> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
@@ -1,26 +1,141 @@
-use crate::app::config::{AppChainConfig, ConfigVersion};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use bollard::models::{HostConfig, HostConfigLogConfig, PortBinding};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::app::config::{default_madara_repo, AppChainConfig};
+use crate::binary::provider::{get_cached_versions, MadaraBinary};
 use crate::da::da_layers::DALayer;
 use crate::utils::cmd::execute_cmd;
-use crate::utils::constants::{APP_DA_CONFIG_NAME, MADARA_REPO_NAME, MADARA_REPO_ORG};
-use crate::utils::errors::MadaraError;
-use crate::utils::github::git_clone;
-use crate::utils::paths::{get_app_home, get_madara_home};
+use crate::utils::constants::{MADARA_BRANCH_NAME, MADARA_DOCKER_IMAGE};
+use crate::utils::docker::{container_exists, kill_container, run_docker_image, run_docker_image_to_completion};
+use crate::utils::errors::{GithubError, MadaraError};
+use crate::utils::github::{
+    get_commit_hash, get_local_commit_hash, get_local_repo_path, git_clone, is_clone_of, is_local_tag, is_offline,
+    is_tag, parse_github_repo,
+};
+use crate::utils::keystore::write_unlocked_da_config;
+use crate::utils::logs::get_node_log_path;
+use crate::utils::paths::{get_app_home, get_madara_cache_home};
+use crate::utils::process::{
+    get_node_ports, has_flag, save_node_state, spawn_background, NodeHandle, NodeState, P2P_PORT_FLAG,
+    PROMETHEUS_PORT_FLAG, RPC_PORT_FLAG,
+};
 
 pub const GITHUB_BASE_URL: &str = "https://github.com";
 
-pub fn clone_madara_and_build_repo(config: &AppChainConfig) -> Result<(), MadaraError> {
-    let repo_url = format!("{}/{}/{}", GITHUB_BASE_URL, MADARA_REPO_ORG, MADARA_REPO_NAME);
-    let madara_path = get_madara_home()?.join("madara");
-
-    // there was a bug in Version1 where the incorrect commit was
-    // going inside the toml file, so override with the correct commit
-    // in that case
-    let checkout_commit = match config.config_version {
-        ConfigVersion::Version2 => config.madara_version.as_str(),
-        ConfigVersion::Version1 => "5de416aeb2d9e4297e58f7f2dff99aeae521855e",
+const CONTAINER_BASE_PATH: &str = "/var/lib/madara";
+const CONTAINER_DA_CONFIG_PATH: &str = "/etc/madara/da-config.json";
+/// Host name of the host machine in containers
+const DOCKER_HOST_NAME: &str = "host.docker.internal";
+
+/// Returns the Madara commit the app chain is pinned to
+pub fn get_madara_commit(config: &AppChainConfig) -> &str {
+    config.madara_version.as_str()
+}
+
+/// Tag of the Madara version, or its short commit like git
+pub fn get_madara_version_name(config: &AppChainConfig) -> String {
+    match &config.madara_tag {
+        Some(tag) => tag.clone(),
+        None => config.madara_version.chars().take(7).collect(),
+    }
+}
+
+/// Resolves the Madara version to pin, a tag, branch or commit of the repository. Defaults to
+/// the latest commit of the CLI branch upstream, and of the default branch on forks.
+/// Returns the commit and the tag if the version is one.
+///
+/// Local repositories are resolved without GitHub. In offline mode, the version is resolved
+/// from the clone used to build Madara, or else from the cached binaries.
+pub async fn resolve_madara_version(
+    madara_repo: &str,
+    version: &Option<String>,
+) -> Result<(String, Option<String>), GithubError> {
+    let reference = match version {
+        Some(version) => version.clone(),
+        None if madara_repo == default_madara_repo() => MADARA_BRANCH_NAME.to_string(),
+        None => "HEAD".to_string(),
+    };
+
+    if let Some(path) = get_local_clone(madara_repo)? {
+        match resolve_local_version(&path, &reference) {
+            Ok(resolved) => return Ok(resolved),
+            // the build clone may not have the version yet, a cached binary may
+            Err(err) if get_local_repo_path(madara_repo).is_some() => return Err(err),
+            Err(_) => (),
+        }
+    }
+    if is_offline() {
+        return resolve_cached_version(version, &reference);
+    }
+
+    let (org, repo) = parse_github_repo(madara_repo)?;
+    let commit = get_commit_hash(&org, &repo, &reference).await?;
+    let tag = match version {
+        Some(version) if is_tag(&org, &repo, version).await? => Some(version.clone()),
+        _ => None,
+    };
+
+    Ok((commit, tag))
+}
+
+/// Local repository to read the versions of Madara from: the repository itself when it's a local
+/// mirror, or the clone used to build Madara in offline mode
+pub fn get_local_clone(madara_repo: &str) -> Result<Option<PathBuf>, GithubError> {
+    if let Some(path) = get_local_repo_path(madara_repo) {
+        return Ok(Some(path));
+    }
+
+    let madara_path = get_madara_clone_path(madara_repo)?;
+    Ok((is_offline() && is_clone_of(&madara_path, madara_repo)).then_some(madara_path))
+}
+
+/// Clone used to build Madara from the repository. Each fork has its own clone, so that building
+/// from one doesn't replace the clone of another, while upstream keeps "{cache}/madara".
+pub fn get_madara_clone_path(madara_repo: &str) -> Result<PathBuf, io::Error> {
+    let cache_home = get_madara_cache_home()?;
+    if madara_repo == default_madara_repo() {
+        return Ok(cache_home.join("madara"));
+    }
+    let repo_hash = hex::encode(Sha256::digest(madara_repo.as_bytes()));
+    Ok(cache_home.join(format!("madara-{}", &repo_hash[..16])))
+}
+
+fn resolve_local_version(path: &Path, reference: &str) -> Result<(String, Option<String>), GithubError> {
+    let commit = get_local_commit_hash(path, reference)?;
+    let tag = is_local_tag(path, reference)?.then(|| reference.to_string());
+    Ok((commit, tag))
+}
+
+/// Picks a cached binary, the one of the commit if a version is given or the last cached one
+fn resolve_cached_version(version: &Option<String>, reference: &str) -> Result<(String, Option<String>), GithubError> {
+    let cached_versions = get_cached_versions()?;
+    let commit = match version {
+        // commits may be abbreviated, like git
+        Some(version) if version.len() >= 7 => cached_versions.into_iter().find(|commit| commit.starts_with(version)),
+        Some(_) => None,
+        None => cached_versions.into_iter().next(),
     };
 
-    match git_clone(&repo_url, &madara_path, Some(checkout_commit)) {
+    match commit {
+        Some(commit) => {
+            log::info!("Using the cached Madara binary {} in offline mode", commit);
+            Ok((commit, None))
+        }
+        None => Err(GithubError::ReferenceNotFound(reference.to_string(), "the cached Madara binaries".to_string())),
+    }
+}
+
+pub fn clone_madara_and_build_repo(config: &AppChainConfig) -> Result<(), MadaraError> {
+    let repo_url = &config.madara_repo;
+    let madara_path = get_madara_clone_path(repo_url)?;
+    let checkout_commit = get_madara_commit(config);
+
+    match git_clone(repo_url, &madara_path, Some(checkout_commit)) {
         Ok(_) => {
             log::info!("Successfully cloned Madara repo");
         }
@@ -29,61 +144,262 @@ pub fn clone_madara_and_build_repo(config: &AppChainConfig) -> Result<(), Madara
             return Err(MadaraError::FailedToCloneRepo);
         }
     }
-    execute_cmd("cargo", &["build", "--release", "--features", "avail", "--features", "celestia"], &madara_path)?;
+    let mut build_args = vec!["build", "--release", "--features", "avail", "--features", "celestia"];
+    if is_offline() {
+        // dependencies must already be in the cargo cache
+        build_args.push("--offline");
+    }
+    execute_cmd("cargo", &build_args, &madara_path)?;
 
     Ok(())
 }
 
-pub fn setup_and_run_madara(config: AppChainConfig, madara_flags: &[String]) -> Result<(), MadaraError> {
-    let madara_path = get_madara_home()?.join("madara");
-
-    let app_home = get_app_home(config.app_chain.as_str())?;
-    let binding = app_home.join(APP_DA_CONFIG_NAME);
-    let da_config_path = match binding.to_str() {
-        Some(path) => path,
-        None => {
-            return Err(MadaraError::FailedToGetDAConfig);
-        }
-    };
-
-    let da_conf = format!("--da-conf={}", da_config_path);
-    let base_path = format!("--base-path={}", config.base_path);
-
+/// Arguments to start Madara with, paths must be the ones seen by the node
+fn get_madara_args(
+    config: &AppChainConfig,
+    base_path: &str,
+    da_config_path: &str,
+    madara_flags: &[String],
+) -> Vec<String> {
     let mut args = vec![
-        "--chain=dev",
-        "--alice",
-        "--force-authoring",
-        "--rpc-cors=all",
-        "--tx-ban-seconds=0",
-        "--prometheus-external",
-        "--rpc-external",
-        &base_path,
+        "--chain=dev".to_string(),
+        "--alice".to_string(),
+        "--force-authoring".to_string(),
+        "--rpc-cors=all".to_string(),
+        "--tx-ban-seconds=0".to_string(),
+        "--prometheus-external".to_string(),
+        "--rpc-external".to_string(),
+        format!("--base-path={}", base_path),
     ];
 
-    args.extend(madara_flags.iter().map(|arg| arg.as_str()));
+    // ports passed in the flags take precedence over the config
+    let ports = [
+        (RPC_PORT_FLAG, config.rpc_port),
+        (P2P_PORT_FLAG, config.p2p_port),
+        (PROMETHEUS_PORT_FLAG, config.prometheus_port),
+    ];
+    for (flag, port) in ports {
+        if !has_flag(madara_flags, flag) {
+            args.push(format!("{}={}", flag, port));
+        }
+    }
+    args.extend(madara_flags.iter().cloned());
 
+    let da_conf = format!("--da-conf={}", da_config_path);
     match config.da_layer {
         DALayer::Ethereum => {
-            let ethereum_conf = vec!["--da-layer=ethereum", &da_conf];
-            args.extend(ethereum_conf);
+            args.extend(["--da-layer=ethereum".to_string(), da_conf]);
         }
         DALayer::Avail => {
-            let avail_conf = vec!["--da-layer=avail", &da_conf];
-            args.extend(avail_conf);
+            args.extend(["--da-layer=avail".to_string(), da_conf]);
+        }
+        DALayer::Celestia => {
+            args.extend(["--da-layer=celestia".to_string(), da_conf]);
         }
         _ => (),
     }
 
-    let config_path =
-        madara_path.join("configs").into_os_string().into_string().map_err(MadaraError::FailedToConvertToString)?;
+    args
+}
+
+/// Arguments of the `setup` command, which fetches the chain specs into the base path
+fn get_setup_args(base_path: &str, configs_path: Option<String>) -> Vec<String> {
+    let mut args = vec!["setup".to_string(), "--chain=dev".to_string(), format!("--base-path={}", base_path)];
+    match configs_path {
+        Some(configs_path) => args.extend(["--from-local".to_string(), configs_path]),
+        // prebuilt binaries don't ship the chain configs
+        None => args.push("--from-remote".to_string()),
+    }
+    args
+}
+
+fn to_str_args(args: &[String]) -> Vec<&str> {
+    args.iter().map(|arg| arg.as_str()).collect()
+}
+
+fn path_to_string(path: &Path) -> Result<String, MadaraError> {
+    match path.to_str() {
+        Some(path) => Ok(path.to_string()),
+        None => Err(MadaraError::FailedToGetDAConfig),
+    }
+}
+
+/// Runs `madara setup`, which fetches the chain specs into the base path
+pub fn setup_madara(config: &AppChainConfig, binary: &MadaraBinary) -> Result<(), MadaraError> {
+    let binary_path =
+        binary.path.clone().into_os_string().into_string().map_err(MadaraError::FailedToConvertToString)?;
+    let configs_path = match &binary.configs_path {
+        Some(configs_path) => Some(configs_path.clone()),
+        // the remote configs can't be fetched offline, use the ones of the build clone
+        None if is_offline() => {
+            Some(get_madara_clone_path(&config.madara_repo)?.join("configs")).filter(|path| path.is_dir())
+        }
+        None => None,
+    };
+    let configs_path = match configs_path {
+        Some(configs_path) => {
+            Some(configs_path.into_os_string().into_string().map_err(MadaraError::FailedToConvertToString)?)
+        }
+        None => None,
+    };
+
+    let app_home = get_app_home(config.app_chain.as_str())?;
+    let setup_args = get_setup_args(&config.base_path, configs_path);
+    execute_cmd(&binary_path, &to_str_args(&setup_args), &app_home)?;
+
+    Ok(())
+}
+
+/// Sets up the chain and starts Madara as a background process, reading the DA config
+/// at `da_config_path`. Returns the state of the started node, which is also persisted
+/// in the app home.
+pub fn setup_and_run_madara(
+    config: AppChainConfig,
+    binary: &MadaraBinary,
+    da_config_path: &Path,
+    madara_flags: &[String],
+) -> Result<NodeState, MadaraError> {
+    let binary_path =
+        binary.path.clone().into_os_string().into_string().map_err(MadaraError::FailedToConvertToString)?;
+    let app_home = get_app_home(config.app_chain.as_str())?;
+    let da_config_path = path_to_string(da_config_path)?;
+
+    setup_madara(&config, binary)?;
+
+    let args = get_madara_args(&config, &config.base_path, &da_config_path, madara_flags);
+    let log_file = get_node_log_path(&config.app_chain)?;
+    let pid = spawn_background(&binary_path, &to_str_args(&args), &app_home, &log_file, &config.logs)?;
+
+    let state =
+        NodeState::new(NodeHandle::Process(pid), &config, madara_flags, Some(log_file), Some(binary.path.clone()));
+    save_node_state(&config.app_chain, &state)?;
+
+    Ok(state)
+}
+
+pub fn get_container_name(app_chain: &str) -> String {
+    format!("madara-{}", app_chain)
+}
+
+/// Image of the Madara repository, forks on GitHub publish theirs under their own org
+fn get_docker_image(config: &AppChainConfig) -> String {
+    let image = match parse_github_repo(&config.madara_repo) {
+        Ok((org, repo)) => format!("ghcr.io/{}/{}", org.to_lowercase(), repo.to_lowercase()),
+        Err(_) => MADARA_DOCKER_IMAGE.to_string(),
+    };
+    format!("{}:{}", image, get_madara_commit(config))
+}
+
+/// Host paths mounted in the Madara container, the DA config is only needed to run the node
+fn get_docker_binds(config: &AppChainConfig, da_config_path: Option<&Path>) -> Result<Vec<String>, MadaraError> {
+    fs::create_dir_all(&config.base_path)?;
+
+    let mut binds = vec![format!("{}:{}", config.base_path, CONTAINER_BASE_PATH)];
+    if let Some(da_config_path) = da_config_path {
+        binds.push(format!("{}:{}:ro", path_to_string(da_config_path)?, CONTAINER_DA_CONFIG_PATH));
+    }
+
+    Ok(binds)
+}
+
+/// Writes the DA config handed to the node container, where localhost is the container itself.
+/// Endpoints on the localhost of the host are reached through `host.docker.internal` instead.
+fn write_docker_da_config(app_chain: &str, da_config_path: &Path) -> Result<PathBuf, MadaraError> {
+    // DA layers without a config
+    if !da_config_path.is_file() {
+        return Ok(da_config_path.to_path_buf());
+    }
+
+    let mut da_config: Value = serde_json::from_str(&fs::read_to_string(da_config_path)?)?;
+    rewrite_localhost_urls(&mut da_config);
+    Ok(write_unlocked_da_config(app_chain, &da_config)?)
+}
+
+fn rewrite_localhost_urls(value: &mut Value) {
+    match value {
+        Value::String(url) => {
+            if let Some(docker_url) = to_docker_host_url(url) {
+                *url = docker_url;
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(rewrite_localhost_urls),
+        Value::Object(values) => values.values_mut().for_each(rewrite_localhost_urls),
+        _ => (),
+    }
+}
 
-    execute_cmd(
-        "./target/release/madara",
-        &["setup", "--chain=dev", "--from-local", config_path.as_str(), &base_path],
-        &madara_path,
-    )?;
+/// Replaces the host of the URL by `host.docker.internal` if it's localhost, e.g.
+/// "http://localhost:8545" becomes "http://host.docker.internal:8545"
+fn to_docker_host_url(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let host_end = rest.find([':', '/']).unwrap_or(rest.len());
+    match &rest[..host_end] {
+        "localhost" | "127.0.0.1" => Some(format!("{}://{}{}", scheme, DOCKER_HOST_NAME, &rest[host_end..])),
+        _ => None,
+    }
+}
 
-    execute_cmd("./target/release/madara", args.as_slice(), &madara_path)?;
+/// Runs `madara setup` inside a Docker container
+pub async fn setup_madara_in_docker(config: &AppChainConfig) -> Result<(), MadaraError> {
+    let setup_args = get_setup_args(CONTAINER_BASE_PATH, None);
+    let setup_host_config = HostConfig { binds: Some(get_docker_binds(config, None)?), ..Default::default() };
+    run_docker_image_to_completion(
+        &get_docker_image(config),
+        &format!("{}-setup", get_container_name(&config.app_chain)),
+        to_str_args(&setup_args),
+        Some(setup_host_config),
+    )
+    .await?;
 
     Ok(())
 }
+
+/// Sets up the chain and starts Madara inside a Docker container, using the image
+/// matching the app chain's Madara version. The base path and the DA config at
+/// `da_config_path` are mounted from the host, and the RPC, P2P and Prometheus
+/// ports are published.
+pub async fn setup_and_run_madara_in_docker(
+    config: AppChainConfig,
+    da_config_path: &Path,
+    madara_flags: &[String],
+) -> Result<NodeState, MadaraError> {
+    let image = get_docker_image(&config);
+    let container_name = get_container_name(&config.app_chain);
+    let da_config_path = write_docker_da_config(&config.app_chain, da_config_path)?;
+    let binds = get_docker_binds(&config, Some(&da_config_path))?;
+
+    setup_madara_in_docker(&config).await?;
+    // the container of a node which exited is kept until the next run
+    if container_exists(&container_name).await? {
+        kill_container(&container_name).await?;
+    }
+
+    let (rpc_port, p2p_port, prometheus_port) = get_node_ports(&config, madara_flags);
+    let mut port_bindings = HashMap::new();
+    for port in [rpc_port, p2p_port, prometheus_port] {
+        port_bindings.insert(
+            format!("{}/tcp", port),
+            Some(vec![PortBinding { host_ip: Some("0.0.0.0".to_string()), host_port: Some(port.to_string()) }]),
+        );
+    }
+    // Docker counts the current log file in `max-file`
+    let log_options = HashMap::from([
+        ("max-size".to_string(), format!("{}m", config.logs.max_size_mb.max(1))),
+        ("max-file".to_string(), (config.logs.max_files + 1).to_string()),
+    ]);
+    let host_config = HostConfig {
+        binds: Some(binds),
+        port_bindings: Some(port_bindings),
+        extra_hosts: Some(vec![format!("{}:host-gateway", DOCKER_HOST_NAME)]),
+        log_config: Some(HostConfigLogConfig { typ: Some("json-file".to_string()), config: Some(log_options) }),
+        ..Default::default()
+    };
+
+    let args = get_madara_args(&config, CONTAINER_BASE_PATH, CONTAINER_DA_CONFIG_PATH, madara_flags);
+    run_docker_image(&image, &container_name, None, Some(to_str_args(&args)), Some(host_config)).await?;
+
+    let state = NodeState::new(NodeHandle::Container(container_name), &config, madara_flags, None, None);
+    save_node_state(&config.app_chain, &state)?;
+
+    Ok(state)
+}
@@ -14,3 +14,8 @@ pub mod constants;
 
 pub mod docker;
 pub mod serde;
+pub mod keystore;
+pub mod process;
+pub mod logs;
+pub mod health;
+pub mod ports;
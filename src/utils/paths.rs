@@ -1,15 +1,102 @@
-use std::fs;
 use std::io::{Error, ErrorKind};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Once, OnceLock};
+use std::{env, fs};
 
-pub fn get_madara_home() -> Result<PathBuf, Error> {
-    if let Some(home_dir) = dirs::home_dir() {
-        let madara_home = home_dir.join(".madara");
-        fs::create_dir_all(&madara_home)?;
-        return Ok(madara_home);
+use inquire::InquireError;
+
+use crate::cli::prompt::{get_boolean_input, CONFIRM_DELETE_BASE_PATH};
+
+/// Home set with `--home`, takes precedence over `MADARA_HOME`
+static MADARA_HOME: OnceLock<PathBuf> = OnceLock::new();
+
+/// Directory Madara keeps the chain data in, under the base path
+const BASE_PATH_CHAINS_DIR: &str = "chains";
+
+/// The previous cache is only looked for once per run
+static LEGACY_CACHE: Once = Once::new();
+
+/// Overrides the home of the CLI, e.g. to isolate CI jobs
+pub fn set_madara_home(madara_home: PathBuf) -> Result<(), Error> {
+    MADARA_HOME.set(madara_home).map_err(|_| Error::new(ErrorKind::AlreadyExists, "Madara home is already set"))
+}
+
+/// Home set with `--home` or `MADARA_HOME`, if any. A relative `MADARA_HOME` is resolved against
+/// the current directory, like `--home`.
+fn get_madara_home_override() -> Result<Option<PathBuf>, Error> {
+    if let Some(madara_home) = MADARA_HOME.get() {
+        return Ok(Some(madara_home.clone()));
+    }
+    match env::var_os("MADARA_HOME").filter(|home| !home.is_empty()) {
+        Some(madara_home) => Ok(Some(env::current_dir()?.join(madara_home))),
+        None => Ok(None),
     }
+}
+
+/// Home of the CLI when it isn't overridden
+fn get_default_madara_home() -> Result<PathBuf, Error> {
+    Ok(dirs::home_dir().ok_or(Error::new(ErrorKind::NotFound, "Failed to get the home directory"))?.join(".madara"))
+}
+
+pub fn get_madara_home() -> Result<PathBuf, Error> {
+    let madara_home = match get_madara_home_override()? {
+        Some(madara_home) => madara_home,
+        None => get_default_madara_home()?,
+    };
+    fs::create_dir_all(&madara_home)?;
 
-    Err(Error::new(ErrorKind::NotFound, "Failed to get the home directory"))
+    Ok(madara_home)
+}
+
+/// Directory of the downloaded binaries and the Madara build. Follows `XDG_CACHE_HOME` unless
+/// the home is overridden, which keeps everything in it.
+pub fn get_madara_cache_home() -> Result<PathBuf, Error> {
+    let xdg_cache_home = env::var_os("XDG_CACHE_HOME").filter(|cache_home| !cache_home.is_empty());
+    let cache_home = match (get_madara_home_override()?, xdg_cache_home) {
+        (None, Some(xdg_cache_home)) => {
+            let cache_home = PathBuf::from(xdg_cache_home).join("madara");
+            fs::create_dir_all(&cache_home)?;
+            let madara_home = get_default_madara_home()?;
+            LEGACY_CACHE.call_once(|| move_legacy_cache(&madara_home, &cache_home));
+            cache_home
+        }
+        _ => get_madara_home()?,
+    };
+    fs::create_dir_all(&cache_home)?;
+
+    Ok(cache_home)
+}
+
+/// Moves the binaries and the Madara clones cached in the home before `XDG_CACHE_HOME` was
+/// followed. The ones which can't be moved are left in place, and where they are is logged.
+fn move_legacy_cache(madara_home: &Path, cache_home: &Path) {
+    let entries = match fs::read_dir(madara_home) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_cache = name == "bin" || name == "madara" || name.starts_with("madara-");
+        if !is_cache || !entry.path().is_dir() {
+            continue;
+        }
+
+        let target = cache_home.join(&name);
+        if target.exists() {
+            log::warn!(
+                "The previous cache {} was not moved, {} already exists",
+                entry.path().display(),
+                target.display()
+            );
+            continue;
+        }
+        match fs::rename(entry.path(), &target) {
+            Ok(_) => log::info!("Moved the cache {} to {}", entry.path().display(), target.display()),
+            Err(err) => {
+                log::warn!("Failed to move the cache {} to {}: {}", entry.path().display(), target.display(), err)
+            }
+        }
+    }
 }
 
 pub fn get_app_chains_home() -> Result<PathBuf, Error> {
@@ -31,3 +118,27 @@ pub fn get_app_home(app: &str) -> Result<PathBuf, Error> {
 
     Ok(app_home)
 }
+
+/// Whether the base path of the app chain may be removed. The default one in the app home is, but
+/// it can be set to any directory, so one which doesn't hold chain data is only removed after a
+/// second confirmation, and never with `--force`. One holding the app home is always kept.
+pub fn confirm_remove_base_path(base_path: &Path, app_home: &Path, force: bool) -> Result<bool, InquireError> {
+    if app_home.starts_with(base_path) {
+        log::warn!("Kept {}, it holds the config of the app chain", base_path.display());
+        return Ok(false);
+    }
+    if base_path.starts_with(app_home) || base_path.join(BASE_PATH_CHAINS_DIR).is_dir() {
+        return Ok(true);
+    }
+
+    let confirmed = !force
+        && get_boolean_input(
+            CONFIRM_DELETE_BASE_PATH,
+            &format!("{} doesn't look like chain data, delete it anyway?", base_path.display()),
+            Some(false),
+        )?;
+    if !confirmed {
+        log::warn!("Kept {}, delete it by hand if it isn't needed anymore", base_path.display());
+    }
+    Ok(confirmed)
+}
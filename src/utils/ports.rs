@@ -0,0 +1,11 @@
+use std::net::TcpListener;
+
+/// Whether the port can be listened on, i.e. no other process uses it on any interface
+pub fn is_port_free(port: u16) -> bool {
+    TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+/// Returns the first port from `start` which is free on the host and not in `reserved`
+pub fn find_free_port(start: u16, reserved: &[u16]) -> Option<u16> {
+    (start..=u16::MAX).find(|port| !reserved.contains(port) && is_port_free(*port))
+}
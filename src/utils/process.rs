@@ -0,0 +1,330 @@
+use std::os::fd::OwnedFd;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, fmt, fs, io};
+
+use nix::errno::Errno;
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use serde::{Deserialize, Serialize};
+
+use crate::app::config::{AppChainConfig, LogsConfig};
+use crate::da::da_layers::DALayer;
+use crate::utils::constants::APP_NODE_STATE_NAME;
+use crate::utils::docker::is_container_running;
+use crate::utils::errors::ProcessError;
+use crate::utils::keystore::remove_unlocked_da_config;
+use crate::utils::paths::get_app_home;
+
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub const RPC_PORT_FLAG: &str = "--rpc-port";
+pub const P2P_PORT_FLAG: &str = "--port";
+pub const PROMETHEUS_PORT_FLAG: &str = "--prometheus-port";
+
+/// How to reach the running node, depending on its runtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NodeHandle {
+    Process(u32),
+    Container(String),
+}
+
+impl fmt::Display for NodeHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeHandle::Process(pid) => write!(f, "pid {}", pid),
+            NodeHandle::Container(name) => write!(f, "container {}", name),
+        }
+    }
+}
+
+/// State of a running app chain node, persisted in the app home
+/// so that later invocations of the CLI can find and manage it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeState {
+    pub handle: NodeHandle,
+    /// Unix timestamp (in seconds) at which the node was started
+    pub started_at: u64,
+    /// Start time of the node process in clock ticks since boot, which tells it apart from a
+    /// later process reusing its pid. Unset for containers and states saved before it was added.
+    #[serde(default)]
+    pub process_start_time: Option<u64>,
+    pub da_layer: DALayer,
+    /// Extra flags the node was started with, reused on restart
+    pub madara_flags: Vec<String>,
+    pub rpc_port: u16,
+    pub p2p_port: u16,
+    pub prometheus_port: u16,
+    /// Output of native nodes, containers log through Docker
+    pub log_file: Option<PathBuf>,
+    /// Madara binary the node runs, reused on restart
+    pub binary: Option<PathBuf>,
+}
+
+impl NodeState {
+    pub fn new(
+        handle: NodeHandle,
+        config: &AppChainConfig,
+        madara_flags: &[String],
+        log_file: Option<PathBuf>,
+        binary: Option<PathBuf>,
+    ) -> Self {
+        let (rpc_port, p2p_port, prometheus_port) = get_node_ports(config, madara_flags);
+        let process_start_time = match handle {
+            NodeHandle::Process(pid) => get_process_start_time(pid),
+            NodeHandle::Container(_) => None,
+        };
+        NodeState {
+            handle,
+            started_at: now(),
+            process_start_time,
+            da_layer: config.da_layer.clone(),
+            madara_flags: madara_flags.to_vec(),
+            rpc_port,
+            p2p_port,
+            prometheus_port,
+            log_file,
+            binary,
+        }
+    }
+
+    pub fn uptime(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.started_at))
+    }
+
+    pub async fn is_running(&self) -> Result<bool, ProcessError> {
+        match &self.handle {
+            NodeHandle::Process(pid) => Ok(is_node_process(*pid, self.process_start_time)),
+            NodeHandle::Container(name) => Ok(is_container_running(name).await?),
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default()
+}
+
+/// Returns the RPC, P2P and Prometheus ports the node listens on. Ports passed in the flags
+/// take precedence over the ones of the config.
+pub fn get_node_ports(config: &AppChainConfig, madara_flags: &[String]) -> (u16, u16, u16) {
+    (
+        get_port_flag(madara_flags, RPC_PORT_FLAG).unwrap_or(config.rpc_port),
+        get_port_flag(madara_flags, P2P_PORT_FLAG).unwrap_or(config.p2p_port),
+        get_port_flag(madara_flags, PROMETHEUS_PORT_FLAG).unwrap_or(config.prometheus_port),
+    )
+}
+
+/// Whether the flag is passed, either as `--flag=value` or `--flag value`
+pub fn has_flag(flags: &[String], name: &str) -> bool {
+    flags.iter().any(|flag| flag == name || flag.strip_prefix(name).is_some_and(|value| value.starts_with('=')))
+}
+
+/// Reads a port passed either as `--flag=port` or `--flag port`
+fn get_port_flag(flags: &[String], name: &str) -> Option<u16> {
+    let prefix = format!("{}=", name);
+    flags.iter().enumerate().find_map(|(i, flag)| match flag.strip_prefix(&prefix) {
+        Some(value) => value.parse().ok(),
+        None if flag == name => flags.get(i + 1).and_then(|value| value.parse().ok()),
+        None => None,
+    })
+}
+
+fn get_state_path(app: &str) -> Result<PathBuf, ProcessError> {
+    Ok(get_app_home(app)?.join(APP_NODE_STATE_NAME))
+}
+
+pub fn save_node_state(app: &str, state: &NodeState) -> Result<(), ProcessError> {
+    fs::write(get_state_path(app)?, serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+pub fn remove_node_state(app: &str) -> Result<(), ProcessError> {
+    let state_path = get_state_path(app)?;
+    if state_path.exists() {
+        fs::remove_file(state_path)?;
+    }
+    Ok(())
+}
+
+/// Returns the state of the app chain node if it is still running.
+/// A state file left behind by a node that has exited is cleaned up, along with its
+/// unlocked DA config.
+pub async fn get_running_node(app: &str) -> Result<Option<NodeState>, ProcessError> {
+    let state_path = get_state_path(app)?;
+    if !state_path.exists() {
+        return Ok(None);
+    }
+
+    let state: NodeState = serde_json::from_str(&fs::read_to_string(state_path)?)?;
+    if state.is_running().await? {
+        return Ok(Some(state));
+    }
+
+    log::debug!("Removing stale node state of {}", app);
+    remove_node_state(app)?;
+    remove_unlocked_da_config(app)?;
+    Ok(None)
+}
+
+/// Whether a process with the pid exists and can be signalled by the current user. Processes of
+/// other users can't be nodes started by the CLI.
+pub fn is_process_running(pid: u32) -> bool {
+    kill(Pid::from_raw(pid as i32), None).is_ok()
+}
+
+/// Fields of `/proc/<pid>/stat` following the name of the process, starting with its state
+fn get_process_stat(pid: u32) -> Option<Vec<String>> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // the name of the process may contain spaces and parentheses, the fields follow the last one
+    let (_, fields) = stat.rsplit_once(')')?;
+    Some(fields.split_whitespace().map(String::from).collect())
+}
+
+/// Start time of the process in clock ticks since boot, read from `/proc/<pid>/stat`
+pub fn get_process_start_time(pid: u32) -> Option<u64> {
+    // fields are numbered from 1 and the start time is the 22nd, the one after the name is the 3rd
+    get_process_stat(pid)?.get(22 - 3)?.parse().ok()
+}
+
+/// Whether the process exited and waits to be reaped, e.g. a node started by this run of the CLI
+/// which crashed. It can still be signalled but isn't running anymore.
+fn is_zombie(pid: u32) -> bool {
+    get_process_stat(pid).is_some_and(|stat| stat.first().is_some_and(|state| state == "Z"))
+}
+
+/// Whether the pid is still the node process, and not another process which reused the pid
+/// after the node exited or the machine rebooted
+pub fn is_node_process(pid: u32, start_time: Option<u64>) -> bool {
+    is_process_running(pid)
+        && !is_zombie(pid)
+        && start_time.is_none_or(|start_time| get_process_start_time(pid) == Some(start_time))
+}
+
+/// Spawns the program detached from the current terminal, so that it
+/// keeps running after the CLI exits. Output is piped to a `madara log-writer`
+/// process, which appends it to `log_file` and rotates it.
+pub fn spawn_background(
+    program: &str,
+    args: &[&str],
+    dir: &PathBuf,
+    log_file: &Path,
+    logs: &LogsConfig,
+) -> Result<u32, ProcessError> {
+    let mut log_writer = Command::new(env::current_exe()?)
+        .arg("log-writer")
+        .arg("--file")
+        .arg(log_file)
+        .args(["--max-size-mb", &logs.max_size_mb.to_string(), "--max-files", &logs.max_files.to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()?;
+    let log: OwnedFd = log_writer.stdin.take().ok_or_else(|| io::Error::other("Log writer has no input"))?.into();
+
+    let child = Command::new(program)
+        .current_dir(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .process_group(0)
+        .spawn()?;
+
+    log::debug!("Spawned {} with pid {}", program, child.id());
+    Ok(child.id())
+}
+
+/// Sends SIGTERM to the node process and waits for it to exit. Falls back to SIGKILL if it is
+/// still alive after `timeout`. The process is only signalled while it matches `start_time`.
+pub async fn stop_process(pid: u32, start_time: Option<u64>, timeout: Duration) -> Result<(), ProcessError> {
+    if !is_node_process(pid, start_time) {
+        return Ok(());
+    }
+    let process = Pid::from_raw(pid as i32);
+    kill(process, Signal::SIGTERM).map_err(|err| ProcessError::FailedToSignal(pid, err))?;
+
+    let mut waited = Duration::ZERO;
+    while waited < timeout {
+        if !is_node_process(pid, start_time) {
+            return Ok(());
+        }
+        tokio::time::sleep(STOP_POLL_INTERVAL).await;
+        waited += STOP_POLL_INTERVAL;
+    }
+
+    log::warn!("Process {} did not exit after {}s, sending SIGKILL", pid, timeout.as_secs());
+    match kill(process, Signal::SIGKILL) {
+        Ok(_) | Err(Errno::ESRCH) => Ok(()),
+        Err(err) => Err(ProcessError::FailedToSignal(pid, err)),
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes, seconds) = (secs / 86400, (secs % 86400) / 3600, (secs % 3600) / 60, secs % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{}s", seconds),
+        (0, 0, _) => format!("{}m {}s", minutes, seconds),
+        (0, _, _) => format!("{}h {}m {}s", hours, minutes, seconds),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn flags(flags: &[&str]) -> Vec<String> {
+        flags.iter().map(|flag| flag.to_string()).collect()
+    }
+
+    #[test]
+    fn get_port_flag_with_equals() {
+        assert_eq!(get_port_flag(&flags(&["--rpc-port=9950"]), RPC_PORT_FLAG), Some(9950));
+    }
+
+    #[test]
+    fn get_port_flag_with_separate_value() {
+        assert_eq!(get_port_flag(&flags(&["--name", "test", "--rpc-port", "9950"]), RPC_PORT_FLAG), Some(9950));
+    }
+
+    #[test]
+    fn get_port_flag_ignores_other_flags() {
+        // "--port" is a prefix of "--port-range" but a different flag
+        assert_eq!(get_port_flag(&flags(&["--port-range=1", "--rpc-port", "9950"]), P2P_PORT_FLAG), None);
+        assert_eq!(get_port_flag(&flags(&["--rpc-port"]), RPC_PORT_FLAG), None);
+        assert_eq!(get_port_flag(&flags(&["--rpc-port", "abc"]), RPC_PORT_FLAG), None);
+        assert_eq!(get_port_flag(&flags(&["--rpc-port=70000"]), RPC_PORT_FLAG), None);
+    }
+
+    #[test]
+    fn exited_child_is_not_running() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        let start_time = get_process_start_time(pid);
+        // the child isn't reaped until `wait`, it stays a zombie meanwhile
+        for _ in 0..50 {
+            if is_zombie(pid) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert!(is_process_running(pid));
+        assert!(!is_node_process(pid, start_time));
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn has_flag_in_both_forms() {
+        assert!(has_flag(&flags(&["--rpc-port=9950"]), RPC_PORT_FLAG));
+        assert!(has_flag(&flags(&["--rpc-port", "9950"]), RPC_PORT_FLAG));
+        assert!(!has_flag(&flags(&["--rpc-port-cors=*"]), RPC_PORT_FLAG));
+        assert!(!has_flag(&flags(&["--prometheus-port", "9616"]), RPC_PORT_FLAG));
+        assert!(!has_flag(&[], RPC_PORT_FLAG));
+    }
+}
@@ -1,17 +1,47 @@
 use std::fs;
 
-use crate::app::config::AppChainConfig;
+use toml::{Table, Value};
+
+use crate::app::config::{AppChainConfig, ConfigVersion};
+use crate::app::migrations::{get_config_version, migrate};
 use crate::utils::constants::APP_CONFIG_NAME;
 use crate::utils::errors::TomlError;
 use crate::utils::paths::get_app_home;
 
 pub fn regenerate_app_config(app: &str) -> Result<(AppChainConfig, bool), TomlError> {
-    let app_home = get_app_home(app)?;
-    let app_config = app_home.join(APP_CONFIG_NAME);
-    let toml_content = fs::read_to_string(app_config)?;
+    let (config, _) = migrate_app_config(app, false)?;
 
-    match toml::from_str::<AppChainConfig>(&toml_content) {
+    match Value::Table(config).try_into::<AppChainConfig>() {
         Ok(app_chain_config) => Ok((app_chain_config, true)),
         Err(err) => Err(TomlError::FailedToParseToml(err)),
     }
 }
+
+/// Migrates the config of the app chain to the latest version, after backing up the current
+/// one. Returns the migrated config and the versions it went through. Nothing is written on
+/// a dry run.
+pub fn migrate_app_config(app: &str, dry_run: bool) -> Result<(Table, Vec<ConfigVersion>), TomlError> {
+    let app_home = get_app_home(app)?;
+    let app_config = app_home.join(APP_CONFIG_NAME);
+    let toml_content = fs::read_to_string(&app_config)?;
+    let mut config: Table = toml::from_str(&toml_content)?;
+
+    let version = get_config_version(&config)?;
+    let versions = migrate(&mut config)?;
+    if versions.is_empty() || dry_run {
+        return Ok((config, versions));
+    }
+
+    let backup = app_home.join(format!("{}.{}.bak", APP_CONFIG_NAME, version.to_string().to_lowercase()));
+    fs::write(&backup, toml_content).map_err(TomlError::FailedToBackupConfig)?;
+    fs::write(&app_config, toml::to_string(&config)?)?;
+    log::debug!("Migrated config of {} from {}, backup saved to {}", app, version, backup.display());
+
+    Ok((config, versions))
+}
+
+pub fn save_app_config(config: &AppChainConfig) -> Result<(), TomlError> {
+    let app_config = get_app_home(&config.app_chain)?.join(APP_CONFIG_NAME);
+    fs::write(app_config, config.to_toml()?)?;
+    Ok(())
+}